@@ -0,0 +1,101 @@
+use crate::utils::encode_base58_checksum;
+
+/// Bitcoin network, which sets the version byte of addresses and keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    /// Version byte of pay to public key hash addresses, 1... on mainnet and m... or n... on
+    /// testnet
+    pub fn p2pkh_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet => 0x6f,
+        }
+    }
+
+    /// Version byte of pay to script hash addresses, 3... on mainnet and 2... on testnet
+    pub fn p2sh_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet => 0xc4,
+        }
+    }
+
+    /// Version byte of private keys in Wallet Import Format
+    pub fn wif_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet => 0xef,
+        }
+    }
+
+    /// Network of a Wallet Import Format version byte, the inverse of `wif_prefix`
+    pub fn from_wif_prefix(prefix: u8) -> Option<Network> {
+        [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.wif_prefix() == prefix)
+    }
+}
+
+/// Legacy address paying to the HASH160 of a public key
+pub fn p2pkh_address(hash160: &[u8; 20], network: Network) -> String {
+    encode_base58_checksum(&[&[network.p2pkh_prefix()], &hash160[..]].concat())
+}
+
+/// Legacy address paying to the HASH160 of a redeem script
+pub fn p2sh_address(script_hash: &[u8; 20], network: Network) -> String {
+    encode_base58_checksum(&[&[network.p2sh_prefix()], &script_hash[..]].concat())
+}
+
+#[cfg(test)]
+mod address_tests {
+    use crate::utils::{decode_base58_checksum, decode_hex};
+
+    use super::*;
+
+    #[test]
+    fn test_wif_prefix() {
+        for network in [Network::Mainnet, Network::Testnet] {
+            assert_eq!(
+                Network::from_wif_prefix(network.wif_prefix()),
+                Some(network)
+            );
+        }
+        assert_eq!(Network::from_wif_prefix(0x00), None);
+        assert_eq!(Network::from_wif_prefix(0x81), None);
+    }
+
+    #[test]
+    fn test_p2sh_address() {
+        let script_hash: [u8; 20] = decode_hex("74d691da1574e6b3c192ecfb52cc8984ee7b6c56")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            p2sh_address(&script_hash, Network::Mainnet),
+            "3CLoMMyuoDQTPRD3XYZtCvgvkadrAdvdXh"
+        );
+        assert_eq!(
+            p2sh_address(&script_hash, Network::Testnet),
+            "2N3u1R6uwQfuobCqbCgBkpsgBxvr1tZpe7B"
+        );
+    }
+
+    #[test]
+    fn test_p2pkh_address() {
+        let hash160: [u8; 20] = decode_hex("f54a5851e9372b87810a8e60cdd2e7cfd80b6e31")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let address = p2pkh_address(&hash160, Network::Mainnet);
+        assert_eq!(address, "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs");
+        assert_eq!(
+            decode_base58_checksum(&p2pkh_address(&hash160, Network::Testnet)).unwrap(),
+            [&[0x6f], &hash160[..]].concat()
+        );
+    }
+}
@@ -0,0 +1,523 @@
+// Big integers used across the crate. The default backend is pure Rust, on top of num-bigint,
+// and the `rug` feature switches to GMP, which is faster but needs a C toolchain.
+// Both expose the same rug-like API, so the rest of the crate doesn't know which one it gets.
+// On the pure Rust backend the operations on references return an Integer right away instead of
+// rug's incomplete values, and `complete` is a no-op
+
+#[cfg(feature = "rug")]
+pub use rug::{integer::Order, Complete, Integer};
+
+#[cfg(not(feature = "rug"))]
+pub use self::pure::{Complete, Integer, Order, ParseIntegerError};
+
+#[cfg(not(feature = "rug"))]
+mod pure {
+    use std::{
+        cmp::Ordering,
+        fmt,
+        ops::{
+            Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Shl, ShlAssign,
+            Shr, ShrAssign, Sub, SubAssign,
+        },
+        str::FromStr,
+    };
+
+    use num_bigint::{BigInt, Sign};
+    use num_integer::Integer as _;
+    use num_traits::{One, Signed, Zero};
+
+    /// Mirrors rug's trait to turn incomplete computations into values. Here they are already
+    /// values
+    pub trait Complete {
+        type Completed;
+
+        fn complete(self) -> Self::Completed;
+    }
+
+    impl Complete for Integer {
+        type Completed = Integer;
+
+        fn complete(self) -> Integer {
+            self
+        }
+    }
+
+    impl Complete for (Integer, Integer) {
+        type Completed = (Integer, Integer);
+
+        fn complete(self) -> (Integer, Integer) {
+            self
+        }
+    }
+
+    /// Digit order for `from_digits` and `to_digits`, most or least significant first
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Order {
+        Msf,
+        Lsf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ParseIntegerError;
+
+    impl fmt::Display for ParseIntegerError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "invalid digit found in string")
+        }
+    }
+
+    impl std::error::Error for ParseIntegerError {}
+
+    #[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Integer(BigInt);
+
+    impl Integer {
+        pub const ZERO: Integer = Integer(BigInt::ZERO);
+
+        pub fn new() -> Self {
+            Integer::ZERO
+        }
+
+        /// Like rug, takes an optional sign but nothing else besides the digits
+        pub fn from_str_radix(str: &str, radix: i32) -> Result<Self, ParseIntegerError> {
+            let (negative, digits) = match str.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, str.strip_prefix('+').unwrap_or(str)),
+            };
+            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix as u32)) {
+                return Err(ParseIntegerError);
+            }
+            let num =
+                BigInt::parse_bytes(digits.as_bytes(), radix as u32).ok_or(ParseIntegerError)?;
+            Ok(Integer(if negative { -num } else { num }))
+        }
+
+        /// Non-negative integer from its bytes
+        pub fn from_digits<T: Digit>(digits: &[T], order: Order) -> Self {
+            let bytes: Vec<u8> = digits.iter().map(|digit| digit.to_u8()).collect();
+            match order {
+                Order::Msf => Integer(BigInt::from_bytes_be(Sign::Plus, &bytes)),
+                Order::Lsf => Integer(BigInt::from_bytes_le(Sign::Plus, &bytes)),
+            }
+        }
+
+        /// Bytes of the absolute value, without leading zeros. Zero has no digits
+        pub fn to_digits<T: Digit>(&self, order: Order) -> Vec<T> {
+            if self.0.is_zero() {
+                return vec![];
+            }
+            let (_, bytes) = match order {
+                Order::Msf => self.0.to_bytes_be(),
+                Order::Lsf => self.0.to_bytes_le(),
+            };
+            bytes.into_iter().map(T::from_u8).collect()
+        }
+
+        pub fn significant_digits<T: Digit>(&self) -> usize {
+            self.to_digits::<T>(Order::Msf).len()
+        }
+
+        /// Bits of the absolute value
+        pub fn significant_bits(&self) -> u32 {
+            self.0.bits() as u32
+        }
+
+        /// Bit of the two's complement representation
+        pub fn get_bit(&self, index: u32) -> bool {
+            self.0.bit(index as u64)
+        }
+
+        pub fn is_odd(&self) -> bool {
+            self.0.is_odd()
+        }
+
+        pub fn is_even(&self) -> bool {
+            self.0.is_even()
+        }
+
+        pub fn is_zero(&self) -> bool {
+            self.0.is_zero()
+        }
+
+        /// The 32 lowest bits of the two's complement representation
+        pub fn to_u32_wrapping(&self) -> u32 {
+            let low = self.0.iter_u32_digits().next().unwrap_or(0);
+            if self.0.is_negative() {
+                low.wrapping_neg()
+            } else {
+                low
+            }
+        }
+
+        pub fn abs(self) -> Self {
+            Integer(self.0.abs())
+        }
+
+        /// Quotient rounded so that the remainder is in [0, |divisor|)
+        pub fn div_rem_euc_ref(&self, divisor: &Integer) -> (Integer, Integer) {
+            let remainder = self.0.mod_floor(&divisor.0.abs());
+            let quotient = (&self.0 - &remainder) / &divisor.0;
+            (Integer(quotient), Integer(remainder))
+        }
+
+        /// self**exponent modulo `modulo`, None if the exponent is negative and self has no
+        /// inverse
+        pub fn pow_mod_ref(&self, exponent: &Integer, modulo: &Integer) -> Option<Integer> {
+            let modulo = modulo.0.abs();
+            if modulo.is_zero() {
+                return None;
+            }
+            if exponent.0.is_negative() {
+                let inverse = self.clone().invert(&Integer(modulo.clone())).ok()?;
+                return Some(Integer(inverse.0.modpow(&-&exponent.0, &modulo)));
+            }
+            Some(Integer(
+                self.0.mod_floor(&modulo).modpow(&exponent.0, &modulo),
+            ))
+        }
+
+        /// Inverse modulo `modulo` in [0, |modulo|), or self back if there is none
+        pub fn invert(self, modulo: &Integer) -> Result<Integer, Integer> {
+            let modulo = modulo.0.abs();
+            if modulo.is_zero() {
+                return Err(self);
+            }
+            let gcd = self.0.mod_floor(&modulo).extended_gcd(&modulo);
+            if modulo.is_one() {
+                return Ok(Integer::ZERO);
+            }
+            if !gcd.gcd.is_one() {
+                return Err(self);
+            }
+            Ok(Integer(gcd.x.mod_floor(&modulo)))
+        }
+    }
+
+    /// Digit types for `from_digits` and `to_digits`, only bytes are supported
+    pub trait Digit: Copy {
+        fn to_u8(self) -> u8;
+        fn from_u8(byte: u8) -> Self;
+    }
+
+    impl Digit for u8 {
+        fn to_u8(self) -> u8 {
+            self
+        }
+
+        fn from_u8(byte: u8) -> Self {
+            byte
+        }
+    }
+
+    impl FromStr for Integer {
+        type Err = ParseIntegerError;
+
+        fn from_str(str: &str) -> Result<Self, ParseIntegerError> {
+            Integer::from_str_radix(str, 10)
+        }
+    }
+
+    impl fmt::Display for Integer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::Display::fmt(&self.0, f)
+        }
+    }
+
+    // Debug prints the number alone, like rug
+    impl fmt::Debug for Integer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::Display::fmt(&self.0, f)
+        }
+    }
+
+    impl fmt::LowerHex for Integer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::LowerHex::fmt(&self.0, f)
+        }
+    }
+
+    impl From<&Integer> for Integer {
+        fn from(integer: &Integer) -> Self {
+            integer.clone()
+        }
+    }
+
+    impl Neg for Integer {
+        type Output = Integer;
+
+        fn neg(self) -> Integer {
+            Integer(-self.0)
+        }
+    }
+
+    impl Neg for &Integer {
+        type Output = Integer;
+
+        fn neg(self) -> Integer {
+            Integer(-&self.0)
+        }
+    }
+
+    macro_rules! primitive {
+        ($($primitive:ty),*) => {$(
+            impl From<$primitive> for Integer {
+                fn from(value: $primitive) -> Self {
+                    Integer(BigInt::from(value))
+                }
+            }
+
+            impl PartialEq<$primitive> for Integer {
+                fn eq(&self, other: &$primitive) -> bool {
+                    self.0 == BigInt::from(*other)
+                }
+            }
+
+            impl PartialEq<Integer> for $primitive {
+                fn eq(&self, other: &Integer) -> bool {
+                    BigInt::from(*self) == other.0
+                }
+            }
+
+            impl PartialOrd<$primitive> for Integer {
+                fn partial_cmp(&self, other: &$primitive) -> Option<Ordering> {
+                    self.0.partial_cmp(&BigInt::from(*other))
+                }
+            }
+
+            impl PartialOrd<Integer> for $primitive {
+                fn partial_cmp(&self, other: &Integer) -> Option<Ordering> {
+                    BigInt::from(*self).partial_cmp(&other.0)
+                }
+            }
+
+            impl Shl<$primitive> for Integer {
+                type Output = Integer;
+
+                fn shl(self, bits: $primitive) -> Integer {
+                    Integer(self.0 << bits)
+                }
+            }
+
+            impl Shl<$primitive> for &Integer {
+                type Output = Integer;
+
+                fn shl(self, bits: $primitive) -> Integer {
+                    Integer(&self.0 << bits)
+                }
+            }
+
+            impl ShlAssign<$primitive> for Integer {
+                fn shl_assign(&mut self, bits: $primitive) {
+                    self.0 <<= bits;
+                }
+            }
+
+            // Rounds towards minus infinity, like an arithmetic shift
+            impl Shr<$primitive> for Integer {
+                type Output = Integer;
+
+                fn shr(self, bits: $primitive) -> Integer {
+                    Integer(self.0 >> bits)
+                }
+            }
+
+            impl Shr<$primitive> for &Integer {
+                type Output = Integer;
+
+                fn shr(self, bits: $primitive) -> Integer {
+                    Integer(&self.0 >> bits)
+                }
+            }
+
+            impl ShrAssign<$primitive> for Integer {
+                fn shr_assign(&mut self, bits: $primitive) {
+                    self.0 >>= bits;
+                }
+            }
+        )*};
+    }
+
+    primitive!(i32, i64, u8, u32, u64, usize);
+
+    // Integer with Integer, references and primitives on either side. Division and remainder
+    // truncate, like rug
+    macro_rules! operator {
+        ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+            impl $trait<Integer> for Integer {
+                type Output = Integer;
+
+                fn $method(self, other: Integer) -> Integer {
+                    Integer(self.0 $op other.0)
+                }
+            }
+
+            impl $trait<&Integer> for Integer {
+                type Output = Integer;
+
+                fn $method(self, other: &Integer) -> Integer {
+                    Integer(self.0 $op &other.0)
+                }
+            }
+
+            impl $trait<Integer> for &Integer {
+                type Output = Integer;
+
+                fn $method(self, other: Integer) -> Integer {
+                    Integer(&self.0 $op other.0)
+                }
+            }
+
+            impl $trait<&Integer> for &Integer {
+                type Output = Integer;
+
+                fn $method(self, other: &Integer) -> Integer {
+                    Integer(&self.0 $op &other.0)
+                }
+            }
+
+            impl $assign_trait<Integer> for Integer {
+                fn $assign_method(&mut self, other: Integer) {
+                    self.0 = std::mem::take(&mut self.0) $op other.0;
+                }
+            }
+
+            impl $assign_trait<&Integer> for Integer {
+                fn $assign_method(&mut self, other: &Integer) {
+                    self.0 = std::mem::take(&mut self.0) $op &other.0;
+                }
+            }
+
+            operator!(@primitive $trait, $method, $assign_trait, $assign_method, $op, i32, i64, u32, u64, usize);
+        };
+        (@primitive $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt, $($primitive:ty),*) => {$(
+            impl $trait<$primitive> for Integer {
+                type Output = Integer;
+
+                fn $method(self, other: $primitive) -> Integer {
+                    Integer(self.0 $op BigInt::from(other))
+                }
+            }
+
+            impl $trait<$primitive> for &Integer {
+                type Output = Integer;
+
+                fn $method(self, other: $primitive) -> Integer {
+                    Integer(&self.0 $op BigInt::from(other))
+                }
+            }
+
+            impl $trait<Integer> for $primitive {
+                type Output = Integer;
+
+                fn $method(self, other: Integer) -> Integer {
+                    Integer(BigInt::from(self) $op other.0)
+                }
+            }
+
+            impl $trait<&Integer> for $primitive {
+                type Output = Integer;
+
+                fn $method(self, other: &Integer) -> Integer {
+                    Integer(BigInt::from(self) $op &other.0)
+                }
+            }
+
+            impl $assign_trait<$primitive> for Integer {
+                fn $assign_method(&mut self, other: $primitive) {
+                    self.0 = std::mem::take(&mut self.0) $op BigInt::from(other);
+                }
+            }
+        )*};
+    }
+
+    operator!(Add, add, AddAssign, add_assign, +);
+    operator!(Sub, sub, SubAssign, sub_assign, -);
+    operator!(Mul, mul, MulAssign, mul_assign, *);
+    operator!(Div, div, DivAssign, div_assign, /);
+    operator!(Rem, rem, RemAssign, rem_assign, %);
+}
+
+#[cfg(test)]
+mod bigint_tests {
+    use super::*;
+
+    // The backends must agree on everything below, which is where rug's conventions differ
+    // from other libraries
+
+    #[test]
+    fn test_euclidean_division() {
+        let divisor = Integer::from(7);
+        for (num, quotient, remainder) in [(20, 2, 6), (-20, -3, 1), (-21, -3, 0), (6, 0, 6)] {
+            let (q, r) = Integer::from(num).div_rem_euc_ref(&divisor).complete();
+            assert_eq!((q, r), (Integer::from(quotient), Integer::from(remainder)));
+        }
+        let (q, r) = Integer::from(-20)
+            .div_rem_euc_ref(&Integer::from(-7))
+            .complete();
+        assert_eq!((q, r), (Integer::from(3), Integer::from(1)));
+        // Truncating operators
+        assert_eq!(Integer::from(-20) / 7, -2);
+        assert_eq!(Integer::from(-20) % 7, -6);
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        let p = Integer::from(223);
+        let a = Integer::from(-5);
+        let a_inv = a.clone().invert(&p).unwrap();
+        assert_eq!(a_inv, 89);
+        assert_eq!((a * a_inv).div_rem_euc_ref(&p).complete().1, 1);
+        assert_eq!(Integer::from(0).invert(&p), Err(Integer::from(0)));
+        assert_eq!(
+            Integer::from(6).invert(&Integer::from(9)),
+            Err(Integer::from(6))
+        );
+        let power = Integer::from(3)
+            .pow_mod_ref(&Integer::from(200), &p)
+            .unwrap()
+            .complete();
+        assert_eq!(power, 218);
+        let power = Integer::from(-3)
+            .pow_mod_ref(&Integer::from(3), &p)
+            .unwrap()
+            .complete();
+        assert_eq!(power, 196);
+        let power = Integer::from(5)
+            .pow_mod_ref(&Integer::from(-1), &p)
+            .unwrap()
+            .complete();
+        assert_eq!(power, 134);
+    }
+
+    #[test]
+    fn test_bits_and_digits() {
+        let a = Integer::from(-6);
+        assert_eq!(a.clone() >> 1, -3);
+        assert_eq!(a.clone() >> 2, -2);
+        assert!(!a.get_bit(0) && a.get_bit(1) && !a.get_bit(2) && a.get_bit(100));
+        assert_eq!(a.to_u32_wrapping(), u32::MAX - 5);
+        assert_eq!(a.significant_bits(), 3);
+        assert_eq!(Integer::from(1) << 64u32, Integer::from(u64::MAX) + 1);
+
+        let b = Integer::from(0x0102_0304);
+        assert_eq!(b.to_digits::<u8>(Order::Msf), [1, 2, 3, 4]);
+        assert_eq!(b.to_digits::<u8>(Order::Lsf), [4, 3, 2, 1]);
+        assert_eq!(Integer::from_digits(&[0u8, 0, 1, 2, 3, 4], Order::Msf), b);
+        assert_eq!(
+            Integer::from(0).to_digits::<u8>(Order::Msf),
+            Vec::<u8>::new()
+        );
+        assert_eq!(b.significant_digits::<u8>(), 4);
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let a = Integer::from_str_radix("-ff", 16).unwrap();
+        assert_eq!(a, -255);
+        assert_eq!(format!("{a} {:x}", Integer::from(255)), "-255 ff");
+        assert_eq!(format!("{a:?}"), "-255");
+        assert!(Integer::from_str_radix("", 10).is_err());
+        assert!(Integer::from_str_radix("12a", 10).is_err());
+        assert!(Integer::from_str_radix("0x12", 16).is_err());
+    }
+}
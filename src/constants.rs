@@ -1,12 +1,16 @@
-use rug::Integer;
-
 use lazy_static::lazy_static;
-use rug::ops::Pow;
 
-use crate::{field_element::FieldElement, point::Point};
+use crate::{
+    bigint::{Integer, Order},
+    curve::Secp256k1,
+    field_element::FieldElement,
+    generator_table::GeneratorTable,
+    glv::Endomorphism,
+    point::Point,
+};
 
 lazy_static! {
-    pub static ref PRIME: Integer = Integer::from(2).pow(256) - 2_u64.pow(32) - 977;
+    pub static ref PRIME: Integer = (Integer::from(1) << 256u32) - 2_u64.pow(32) - 977;
     pub static ref A: FieldElement = FieldElement::new(Integer::from(0));
     pub static ref B: FieldElement = FieldElement::new(Integer::from(7));
     pub static ref GX: FieldElement = FieldElement::new(
@@ -29,4 +33,25 @@ lazy_static! {
         16
     )
     .unwrap();
+    pub static ref N_BYTES: [u8; 32] = N.to_digits::<u8>(Order::Msf).try_into().unwrap();
+    pub static ref H: Integer = Integer::from(1);
+    pub static ref G_TABLE: GeneratorTable<Secp256k1> = GeneratorTable::new(&G);
+    // beta is a cube root of unity mod p and lambda the matching one mod N, with the short
+    // lattice basis from "Guide to Elliptic Curve Cryptography", algorithm 3.74
+    pub static ref GLV: Endomorphism<Secp256k1> = Endomorphism::new(
+        FieldElement::new(hex("7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee")),
+        hex("5363ad4cc05c30e0a5261c028812645a122e22ea20816678df02967c1b23bd72"),
+        (
+            hex("3086d221a7d46bcde86c90e49284eb15"),
+            -hex("e4437ed6010e88286f547fa90abfe4c3"),
+        ),
+        (
+            hex("114ca50f7a8e2f3f657c1108d9d44cfd8"),
+            hex("3086d221a7d46bcde86c90e49284eb15"),
+        ),
+    );
+}
+
+fn hex(digits: &str) -> Integer {
+    Integer::from_str_radix(digits, 16).unwrap()
 }
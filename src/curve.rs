@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+
+use crate::{
+    bigint::Integer,
+    constants::{A, B, G, GLV, G_TABLE, H, N},
+    curve,
+    field_element::{FieldBackend, FieldElement, PrimeField, Secp256k1Field, F13, F223},
+    generator_table::GeneratorTable,
+    glv::Endomorphism,
+    point::Point,
+    secp256k1_field::Secp256k1FieldElement,
+};
+
+/// Short Weierstrass curve y**2 = x**3 + a*x + b over `Field`, together with
+/// the subgroup generated by `generator()`.
+/// Points carry their curve in their type, so adding points from different curves does not compile
+pub trait Curve: Clone + Debug + PartialEq + 'static {
+    type Field: PrimeField;
+    /// Representation of the field used for point arithmetic
+    type Backend: FieldBackend<Self::Field>;
+
+    fn a() -> &'static FieldElement<Self::Field>;
+    fn b() -> &'static FieldElement<Self::Field>;
+    fn generator() -> &'static Point<Self>;
+    /// Order of the generator
+    fn order() -> &'static Integer;
+    /// Number of points on the curve divided by the order of the generator
+    fn cofactor() -> &'static Integer;
+    /// Precomputed multiples of the generator, built on first use
+    fn generator_table() -> &'static GeneratorTable<Self>;
+    /// Endomorphism used to speed up variable-time multiplications, if the curve has one
+    fn endomorphism() -> Option<&'static Endomorphism<Self>> {
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl Curve for Secp256k1 {
+    type Field = Secp256k1Field;
+    type Backend = Secp256k1FieldElement;
+
+    fn a() -> &'static FieldElement {
+        &A
+    }
+
+    fn b() -> &'static FieldElement {
+        &B
+    }
+
+    fn generator() -> &'static Point {
+        &G
+    }
+
+    fn order() -> &'static Integer {
+        &N
+    }
+
+    fn cofactor() -> &'static Integer {
+        &H
+    }
+
+    fn generator_table() -> &'static GeneratorTable<Self> {
+        &G_TABLE
+    }
+
+    fn endomorphism() -> Option<&'static Endomorphism<Self>> {
+        Some(&GLV)
+    }
+}
+
+// y**2 = x**3 + 7 over small fields, used in the book examples and in tests
+curve!(
+    ToyCurve13,
+    F13,
+    a = 0,
+    b = 7,
+    generator = (7, 5),
+    order = 7,
+    cofactor = 1
+);
+curve!(
+    ToyCurve223,
+    F223,
+    a = 0,
+    b = 7,
+    generator = (47, 71),
+    order = 21,
+    cofactor = 12
+);
@@ -1,7 +1,56 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq)]
 pub enum BitcoinError {
     #[error("Point not in curve")]
     PointNotInTheCurve,
+    #[error("Scalar out of range")]
+    ScalarOutOfRange,
+    #[error("Field element out of range")]
+    FieldElementOutOfRange,
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Can't expand a message to {0} bytes")]
+    ExpandLengthTooLarge(usize),
+    #[error("Invalid hex string: {0}")]
+    InvalidHex(String),
+    #[error("Invalid SEC prefix: {0:#04x}")]
+    InvalidSecPrefix(u8),
+    #[error("Invalid SEC length: {0} bytes")]
+    InvalidSecLength(usize),
+    #[error("Invalid DER signature: {0}")]
+    InvalidDer(DerError),
+    #[error("Invalid Base58 character: {0:?}")]
+    InvalidBase58Character(char),
+    #[error("Invalid Base58Check checksum")]
+    InvalidChecksum,
+    #[error("Invalid WIF prefix: {0:#04x}")]
+    InvalidWifPrefix(u8),
+    #[error("Invalid WIF length: {0} bytes")]
+    InvalidWifLength(usize),
+    #[error("Invalid WIF compression flag: {0:#04x}")]
+    InvalidWifCompressionFlag(u8),
+}
+
+/// BIP66 strict DER rules
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DerError {
+    #[error("must be between 8 and 72 bytes long")]
+    InvalidLength,
+    #[error("must start with the 0x30 sequence marker")]
+    MissingSequenceMarker,
+    #[error("sequence length doesn't match the signature length")]
+    SequenceLengthMismatch,
+    #[error("r and s must start with the 0x02 integer marker")]
+    MissingIntegerMarker,
+    #[error("lengths of r and s don't add up to the sequence length")]
+    IntegerLengthMismatch,
+    #[error("r and s can't be empty")]
+    EmptyInteger,
+    #[error("r and s can't be negative")]
+    NegativeInteger,
+    #[error("r and s can't have excess zero padding")]
+    ExcessPadding,
 }
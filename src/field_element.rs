@@ -1,40 +1,228 @@
-use std::ops::{Add, Div, Mul, Sub};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Sub},
+    str::FromStr,
+};
+
+use crate::{
+    bigint::{Complete, Integer},
+    constants::PRIME,
+    errors::BitcoinError::{self, DivisionByZero, FieldElementOutOfRange},
+    prime_field,
+    utils::{parse_hex, parse_integer},
+};
+
+/// Type-level description of a prime field F_p.
+/// Elements of different fields are different types, so mixing them is rejected at compile time
+pub trait PrimeField: Clone + Debug + PartialEq + Eq + 'static {
+    fn prime() -> &'static Integer;
+}
+
+/// Field of the secp256k1 curve, p = 2**256 - 2**32 - 977
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1Field;
 
-use rug::{Complete, Integer};
+impl PrimeField for Secp256k1Field {
+    fn prime() -> &'static Integer {
+        &PRIME
+    }
+}
 
-use crate::constants::PRIME;
+// Small fields used in the book examples and as brute-force oracles in tests
+prime_field!(F13, 13);
+prime_field!(F223, 223);
+
+/// Arithmetic used by the curve formulas. Implemented by `FieldElement` for any field, and by
+/// specialized representations like `Secp256k1FieldElement`
+pub trait FieldBackend<F: PrimeField>:
+    Clone
+    + Debug
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + From<FieldElement<F>>
+    + Into<FieldElement<F>>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool;
+    /// Swaps `a` and `b` if `choice` is set. Fixed-width backends do it without branching on
+    /// `choice`, which constant-time scalar multiplication relies on
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool);
+}
+
+/// Inverts all the elements with a single field inversion (Montgomery's trick), which costs
+/// three multiplications per element instead of an inversion each. Fails if any of them is 0
+pub fn batch_invert<F: PrimeField, E: FieldBackend<F>>(
+    elements: &[E],
+) -> Result<Vec<E>, BitcoinError> {
+    // inverses[i] starts as the product of the elements before i
+    let mut inverses = Vec::with_capacity(elements.len());
+    let mut product = E::one();
+    for element in elements {
+        inverses.push(product.clone());
+        product = product * element.clone();
+    }
+    if product.is_zero() {
+        return Err(DivisionByZero);
+    }
+    // Inverse of the product of the elements up to i, walking back
+    let mut inverse = E::one() / product;
+    for i in (0..elements.len()).rev() {
+        inverses[i] = inverse.clone() * inverses[i].clone();
+        inverse = inverse * elements[i].clone();
+    }
+    Ok(inverses)
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct FieldElement {
+pub struct FieldElement<F: PrimeField = Secp256k1Field> {
     pub num: Integer,
+    field: PhantomData<F>,
 }
 
-impl FieldElement {
+impl<F: PrimeField> FieldElement<F> {
     pub fn new(num: Integer) -> Self {
-        if num < 0 || num >= *PRIME {
-            let (_, normalized_value) = num.div_rem_euc_ref(&PRIME).complete();
+        if num < 0 || num >= *F::prime() {
+            let (_, normalized_value) = num.div_rem_euc_ref(F::prime()).complete();
             FieldElement {
                 num: normalized_value,
+                field: PhantomData,
             }
         } else {
-            FieldElement { num }
+            FieldElement {
+                num,
+                field: PhantomData,
+            }
+        }
+    }
+
+    /// Parses a hex number, with or without 0x prefix. It must be smaller than p
+    pub fn from_hex(str: String) -> Result<Self, BitcoinError> {
+        FieldElement::try_from(parse_hex(&str)?)
+    }
+
+    pub fn prime() -> &'static Integer {
+        F::prime()
+    }
+
+    pub fn pow(&self, exponent: &FieldElement<F>) -> FieldElement<F> {
+        self.pow_integer(&exponent.num)
+    }
+
+    /// Division that returns an error instead of panicking when `other` is 0
+    pub fn checked_div(&self, other: &FieldElement<F>) -> Result<FieldElement<F>, BitcoinError> {
+        match other.num.clone().invert(F::prime()) {
+            Ok(inv) => Ok(FieldElement::new(self.num.clone() * &inv)),
+            Err(_) => Err(DivisionByZero),
+        }
+    }
+
+    /// Legendre symbol: 1 for non-zero squares, -1 for non-squares and 0 for 0
+    pub fn legendre(&self) -> i32 {
+        if self.num == Integer::ZERO {
+            return 0;
+        }
+        let exponent: Integer = (F::prime().clone() - 1) >> 1;
+        if self.pow_integer(&exponent).num == 1 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.legendre() != -1
+    }
+
+    /// A square root, None if the element isn't a square. The other root is its negation
+    pub fn sqrt(&self) -> Option<FieldElement<F>> {
+        if self.legendre() == -1 {
+            return None;
         }
+        let p = F::prime();
+        // p = 3 (mod 4), like secp256k1's p: a**((p + 1) / 4) squared is a**((p + 1) / 2) = a
+        if p.to_u32_wrapping() & 3 == 3 {
+            return Some(self.pow_integer(&((p.clone() + 1) >> 2)));
+        }
+        Some(self.tonelli_shanks())
     }
 
-    pub fn from_hex(str: String) -> Self {
-        FieldElement::new(Integer::from_str_radix(str.strip_prefix("0x").unwrap(), 16).unwrap())
+    // Square root for any odd p, used by the small fields with p = 1 (mod 4)
+    fn tonelli_shanks(&self) -> FieldElement<F> {
+        if self.num == Integer::ZERO {
+            return self.clone();
+        }
+        // p - 1 = q * 2**s with q odd
+        let mut q: Integer = F::prime().clone() - 1;
+        let mut s = 0;
+        while q.is_even() {
+            q >>= 1;
+            s += 1;
+        }
+        let mut non_square = FieldElement::new(Integer::from(2));
+        while non_square.legendre() != -1 {
+            non_square = non_square + FieldElement::new(Integer::from(1));
+        }
+        let mut m = s;
+        let mut c = non_square.pow_integer(&q);
+        let mut t = self.pow_integer(&q);
+        let mut root = self.pow_integer(&((q + 1) >> 1));
+        while t.num != 1 {
+            // Smallest i with t**(2**i) = 1, which is below m
+            let mut i = 0;
+            let mut t_power = t.clone();
+            while t_power.num != 1 {
+                t_power = &t_power * &t_power;
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = &b * &b;
+            }
+            m = i;
+            c = &b * &b;
+            t = &t * &c;
+            root = &root * &b;
+        }
+        root
     }
 
-    pub fn pow(&self, exponent: &FieldElement) -> FieldElement {
-        if let Some(result) = self.num.pow_mod_ref(&exponent.num, &PRIME) {
-            FieldElement::new(Integer::from(result))
+    fn pow_integer(&self, exponent: &Integer) -> FieldElement<F> {
+        if let Some(result) = self.num.pow_mod_ref(exponent, F::prime()) {
+            FieldElement::new(result.complete())
         } else {
             unreachable!()
         }
     }
 }
 
-impl Add<FieldElement> for FieldElement {
+impl<F: PrimeField> FieldBackend<F> for FieldElement<F> {
+    fn zero() -> Self {
+        FieldElement::new(Integer::from(0))
+    }
+
+    fn one() -> Self {
+        FieldElement::new(Integer::from(1))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num == Integer::ZERO
+    }
+
+    // Big integer arithmetic isn't constant time anyway, so there is nothing to gain by avoiding
+    // the branch
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        if choice {
+            std::mem::swap(a, b)
+        }
+    }
+}
+
+impl<F: PrimeField> Add<FieldElement<F>> for FieldElement<F> {
     type Output = Self;
 
     fn add(self, other_field_elem: Self) -> Self {
@@ -42,7 +230,15 @@ impl Add<FieldElement> for FieldElement {
     }
 }
 
-impl Sub<FieldElement> for FieldElement {
+impl<F: PrimeField> Add<&FieldElement<F>> for &FieldElement<F> {
+    type Output = FieldElement<F>;
+
+    fn add(self, other_field_elem: &FieldElement<F>) -> FieldElement<F> {
+        FieldElement::new(self.num.clone() + other_field_elem.num.clone())
+    }
+}
+
+impl<F: PrimeField> Sub<FieldElement<F>> for FieldElement<F> {
     type Output = Self;
 
     fn sub(self, other_field_elem: Self) -> Self {
@@ -50,15 +246,15 @@ impl Sub<FieldElement> for FieldElement {
     }
 }
 
-impl Sub<&FieldElement> for &FieldElement {
-    type Output = FieldElement;
+impl<F: PrimeField> Sub<&FieldElement<F>> for &FieldElement<F> {
+    type Output = FieldElement<F>;
 
-    fn sub(self, other_field_elem: &FieldElement) -> FieldElement {
+    fn sub(self, other_field_elem: &FieldElement<F>) -> FieldElement<F> {
         FieldElement::new(self.num.clone() - other_field_elem.num.clone())
     }
 }
 
-impl Mul<FieldElement> for FieldElement {
+impl<F: PrimeField> Mul<FieldElement<F>> for FieldElement<F> {
     type Output = Self;
 
     fn mul(self, other_field_elem: Self) -> Self {
@@ -66,37 +262,49 @@ impl Mul<FieldElement> for FieldElement {
     }
 }
 
-impl Mul<&FieldElement> for &FieldElement {
-    type Output = FieldElement;
+impl<F: PrimeField> Mul<&FieldElement<F>> for &FieldElement<F> {
+    type Output = FieldElement<F>;
 
-    fn mul(self, other_field_elem: &FieldElement) -> FieldElement {
+    fn mul(self, other_field_elem: &FieldElement<F>) -> FieldElement<F> {
         FieldElement::new(self.num.clone() * other_field_elem.num.clone())
     }
 }
 
-impl Div for FieldElement {
-    type Output = FieldElement;
+// Panics when dividing by 0, like integer division. `checked_div` returns an error instead
+impl<F: PrimeField> Div for FieldElement<F> {
+    type Output = FieldElement<F>;
 
-    #[allow(clippy::suspicious_arithmetic_impl)]
-    fn div(self, other: FieldElement) -> FieldElement {
-        if let Ok(inv) = other.num.invert(&PRIME) {
-            FieldElement::new(self.num * &inv)
-        } else {
-            unreachable!()
-        }
+    fn div(self, other: FieldElement<F>) -> FieldElement<F> {
+        self.checked_div(&other).expect("Division by zero")
     }
 }
 
-impl Div for &FieldElement {
-    type Output = FieldElement;
+impl<F: PrimeField> Div for &FieldElement<F> {
+    type Output = FieldElement<F>;
 
-    #[allow(clippy::suspicious_arithmetic_impl)]
-    fn div(self, other: &FieldElement) -> FieldElement {
-        if let Ok(inv) = other.num.clone().invert(&PRIME) {
-            FieldElement::new(self.num.clone() * &inv)
-        } else {
-            unreachable!()
+    fn div(self, other: &FieldElement<F>) -> FieldElement<F> {
+        self.checked_div(other).expect("Division by zero")
+    }
+}
+
+/// Checks that the number is in [0, p) instead of reducing it like `new`
+impl<F: PrimeField> TryFrom<Integer> for FieldElement<F> {
+    type Error = BitcoinError;
+
+    fn try_from(num: Integer) -> Result<Self, BitcoinError> {
+        if num < 0 || num >= *F::prime() {
+            return Err(FieldElementOutOfRange);
         }
+        Ok(FieldElement::new(num))
+    }
+}
+
+/// Decimal, or hex with 0x prefix. The number must be smaller than p
+impl<F: PrimeField> FromStr for FieldElement<F> {
+    type Err = BitcoinError;
+
+    fn from_str(str: &str) -> Result<Self, BitcoinError> {
+        FieldElement::try_from(parse_integer(str)?)
     }
 }
 #[cfg(test)]
@@ -106,16 +314,16 @@ mod tests {
     use super::*;
     #[test]
     fn new_field_element() {
-        let a = FieldElement::new(PRIME.clone() + 1);
+        let a: FieldElement = FieldElement::new(PRIME.clone() + 1);
         assert_eq!(a.num, Integer::from(1));
 
-        let b = FieldElement::new(Integer::from(-1));
+        let b: FieldElement = FieldElement::new(Integer::from(-1));
         assert_eq!(b.num, (PRIME.clone() - 1));
 
-        let c = FieldElement::new(Integer::from(765));
+        let c: FieldElement = FieldElement::new(Integer::from(765));
         assert_eq!(c.num, Integer::from(765));
 
-        let d = FieldElement::new(Integer::from(-765));
+        let d: FieldElement = FieldElement::new(Integer::from(-765));
         assert_eq!(d.num, (PRIME.clone() - 765));
     }
 
@@ -123,7 +331,8 @@ mod tests {
     fn test_add() {
         let half_prime: Integer = PRIME.clone() / 2;
         assert_eq!(
-            FieldElement::new(half_prime.clone() + 56) + FieldElement::new(Integer::from(51312316)),
+            FieldElement::<Secp256k1Field>::new(half_prime.clone() + 56)
+                + FieldElement::new(Integer::from(51312316)),
             FieldElement::new(
                 Integer::from_str_radix(
                     "57896044618658097711785492504343953926634992332820282019728792003954468648203",
@@ -258,4 +467,138 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_small_field_arithmetic() {
+        assert_eq!(felt!(7, F13) + felt!(12, F13), felt!(6, F13));
+        assert_eq!(felt!(6, F13) - felt!(13, F13), felt!(6, F13));
+        assert_eq!(felt!(3, F13) * felt!(12, F13), felt!(10, F13));
+        assert_eq!(felt!(3, F13).pow(&felt!(3, F13)), felt!(1, F13));
+        assert_eq!(felt!(2, F13) / felt!(7, F13), felt!(4, F13));
+        assert_eq!(felt!(-1, F223), felt!(222, F223));
+        assert_eq!(felt!(192, F223) * felt!(105, F223), felt!(90, F223));
+    }
+
+    #[test]
+    fn test_small_field_brute_force() {
+        for a in 0..13 {
+            for b in 1..13 {
+                let (x, y) = (felt!(a, F13), felt!(b, F13));
+                assert_eq!((x.clone() * y.clone()) / y.clone(), x);
+                assert_eq!((x.clone() - y.clone()) + y.clone(), x);
+                assert_eq!(felt!(a * b % 13, F13), x * y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_field_sqrt() {
+        // 13 = 1 (mod 4) goes through Tonelli-Shanks, 223 = 3 (mod 4) through the exponent
+        for a in 0..13 {
+            let x = felt!(a, F13);
+            let squares = (0..13).filter(|b| b * b % 13 == a).count();
+            assert_eq!(x.is_square(), squares > 0);
+            match x.sqrt() {
+                Some(root) => assert_eq!(&root * &root, x),
+                None => assert_eq!(x.legendre(), -1),
+            }
+        }
+        for a in 0..223 {
+            let x = felt!(a, F223);
+            let squares = (0..223).filter(|b| b * b % 223 == a).count();
+            assert_eq!(x.is_square(), squares > 0);
+            assert_eq!(x.sqrt().is_some(), squares > 0);
+            if let Some(root) = x.sqrt() {
+                assert_eq!(&root * &root, x);
+            }
+        }
+        assert_eq!(felt!(0, F13).legendre(), 0);
+        assert_eq!(felt!(4, F13).legendre(), 1);
+        assert_eq!(felt!(2, F13).legendre(), -1);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        for a in [1u32, 2, 4, 7, 12345, 0x8CEF_7310] {
+            let x = felt!(a);
+            let square = &x * &x;
+            let root = square.sqrt().unwrap();
+            assert!(root == x || root == felt!(0) - x);
+            assert!(square.is_square());
+        }
+        // -1 isn't a square when p = 3 (mod 4)
+        assert_eq!(felt!(-1).sqrt(), None);
+        assert_eq!(felt!(-1).legendre(), -1);
+        assert_eq!(felt!(0).sqrt(), Some(felt!(0)));
+    }
+
+    #[test]
+    fn test_fallible_constructors() {
+        assert_eq!(
+            FieldElement::<F223>::from_hex("0xdf".to_owned()),
+            Err(FieldElementOutOfRange)
+        );
+        assert_eq!(
+            FieldElement::<F223>::from_hex("0xde".to_owned()),
+            Ok(felt!(222, F223))
+        );
+        assert!(matches!(
+            FieldElement::<F223>::from_hex("0xzz".to_owned()),
+            Err(BitcoinError::InvalidNumber(_))
+        ));
+
+        assert_eq!(
+            FieldElement::try_from(Integer::from(12)),
+            Ok(felt!(12, F13))
+        );
+        assert_eq!(
+            FieldElement::<F13>::try_from(Integer::from(13)),
+            Err(FieldElementOutOfRange)
+        );
+        assert_eq!(
+            FieldElement::<F13>::try_from(Integer::from(-1)),
+            Err(FieldElementOutOfRange)
+        );
+
+        assert_eq!("12".parse(), Ok(felt!(12, F13)));
+        assert_eq!("0xc".parse(), Ok(felt!(12, F13)));
+        assert_eq!(
+            "13".parse::<FieldElement<F13>>(),
+            Err(FieldElementOutOfRange)
+        );
+        assert!("twelve".parse::<FieldElement<F13>>().is_err());
+        let p: Result<FieldElement, _> = PRIME.to_string().parse();
+        assert_eq!(p, Err(FieldElementOutOfRange));
+    }
+
+    #[test]
+    fn test_checked_div() {
+        assert_eq!(felt!(2, F13).checked_div(&felt!(7, F13)), Ok(felt!(4, F13)));
+        assert_eq!(
+            felt!(2, F13).checked_div(&felt!(0, F13)),
+            Err(DivisionByZero)
+        );
+        assert_eq!(felt!(0).checked_div(&felt!(0)), Err(DivisionByZero));
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let elements: Vec<FieldElement<F223>> = (1..223).map(|a| felt!(a, F223)).collect();
+        let inverses = batch_invert(&elements).unwrap();
+        for (element, inverse) in elements.iter().zip(&inverses) {
+            assert_eq!(element * inverse, felt!(1, F223));
+        }
+
+        let elements = [felt!(3), felt!(PRIME.clone() - 2), felt!(0x8CEF_7310_u32)];
+        let inverses = batch_invert(&elements).unwrap();
+        for (element, inverse) in elements.iter().zip(inverses) {
+            assert_eq!(inverse, felt!(1) / element.clone());
+        }
+
+        assert_eq!(batch_invert::<F13, FieldElement<F13>>(&[]), Ok(vec![]));
+        assert_eq!(
+            batch_invert(&[felt!(2, F13), felt!(0, F13), felt!(5, F13)]),
+            Err(DivisionByZero)
+        );
+    }
 }
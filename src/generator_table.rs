@@ -0,0 +1,213 @@
+use crate::{
+    bigint::{Complete, Integer, Order},
+    curve::Curve,
+    jacobian::JacobianPoint,
+    point::Point,
+    projective::ProjectivePoint,
+    wnaf::odd_multiples,
+};
+
+const WINDOW_SIZE: usize = 16;
+/// wNAF width used for the generator in double scalar multiplication
+const ODD_MULTIPLES_WIDTH: u32 = 8;
+
+/// Affine multiples of a base point, None for the point at infinity
+type Window<C> = Vec<Option<(<C as Curve>::Backend, <C as Curve>::Backend)>>;
+
+/// Fixed-base window table for a curve generator. Entry j - 1 of window i holds the affine
+/// coordinates of j * 16**i * G (None for the point at infinity, which only happens on tiny
+/// curves), so k * G takes one addition per 4 bit window of k and no doublings.
+/// Also keeps the odd multiples G, 3G, ... 127G used by wNAF in double scalar multiplication
+pub struct GeneratorTable<C: Curve> {
+    windows: Vec<Window<C>>,
+    odd_multiples: Window<C>,
+}
+
+impl<C: Curve> GeneratorTable<C> {
+    pub fn new(generator: &Point<C>) -> Self {
+        // Two 4 bit windows per byte of the order
+        let count = C::order().significant_digits::<u8>() * 2;
+        let mut multiples = Vec::with_capacity(count * (WINDOW_SIZE - 1));
+        let mut base = JacobianPoint::from(generator);
+        for _ in 0..count {
+            let mut multiple = base.clone();
+            for _ in 1..WINDOW_SIZE {
+                multiples.push(multiple.clone());
+                multiple = &multiple + &base;
+            }
+            base = multiple;
+        }
+        multiples.extend(odd_multiples(
+            &JacobianPoint::from(generator),
+            1 << (ODD_MULTIPLES_WIDTH - 2),
+        ));
+        // All the entries are normalized with a single inversion
+        let mut entries = JacobianPoint::batch_to_affine_coordinates(&multiples);
+        let odd_multiples = entries.split_off(count * (WINDOW_SIZE - 1));
+        let windows = entries
+            .chunks(WINDOW_SIZE - 1)
+            .map(|window| window.to_vec())
+            .collect();
+        GeneratorTable {
+            windows,
+            odd_multiples,
+        }
+    }
+
+    pub(crate) fn odd_multiples(&self) -> &[Option<(C::Backend, C::Backend)>] {
+        &self.odd_multiples
+    }
+
+    pub(crate) fn odd_multiples_width(&self) -> u32 {
+        ODD_MULTIPLES_WIDTH
+    }
+
+    /// 4 bit digits of the scalar reduced modulo the order, least significant first
+    fn digits(&self, scalar: &Integer) -> Vec<usize> {
+        let (_, reduced) = scalar.div_rem_euc_ref(C::order()).complete();
+        let mut digits: Vec<usize> = reduced
+            .to_digits::<u8>(Order::Lsf)
+            .iter()
+            .flat_map(|byte| [(byte & 0x0f) as usize, (byte >> 4) as usize])
+            .collect();
+        digits.resize(self.windows.len(), 0);
+        digits
+    }
+
+    /// Variable time multiplication of the generator, only for public scalars
+    pub(crate) fn mul(&self, scalar: &Integer) -> JacobianPoint<C> {
+        let mut result = JacobianPoint::infinity();
+        for (window, digit) in self.windows.iter().zip(self.digits(scalar)) {
+            if digit == 0 {
+                continue;
+            }
+            if let Some((x, y)) = &window[digit - 1] {
+                result = result.add_mixed(&JacobianPoint::from_affine(x.clone(), y.clone()));
+            }
+        }
+        result
+    }
+
+    /// Constant time multiplication of the generator. Every entry of each window is read and the
+    /// right one is picked with conditional swaps, then added with the complete formulas
+    pub(crate) fn mul_secret(&self, scalar: &Integer) -> ProjectivePoint<C> {
+        let (a, b3) = ProjectivePoint::<C>::formula_constants();
+        let mut result = ProjectivePoint::identity();
+        for (window, digit) in self.windows.iter().zip(self.digits(scalar)) {
+            let mut selected = ProjectivePoint::identity();
+            for (j, entry) in window.iter().enumerate() {
+                let mut candidate = match entry {
+                    Some((x, y)) => ProjectivePoint::from_affine(x.clone(), y.clone()),
+                    None => ProjectivePoint::identity(),
+                };
+                ProjectivePoint::conditional_swap(
+                    &mut selected,
+                    &mut candidate,
+                    ct_eq(j + 1, digit),
+                );
+            }
+            result = result.add(&selected, &a, &b3);
+        }
+        result
+    }
+}
+
+/// Equality of two small values without a data dependent branch
+fn ct_eq(a: usize, b: usize) -> bool {
+    let diff = (a ^ b) as u64;
+    // diff | -diff has the top bit set unless diff is zero
+    ((diff | diff.wrapping_neg()) >> 63) == 0
+}
+
+#[cfg(test)]
+mod generator_table_tests {
+    use std::time::Instant;
+
+    use crate::{
+        constants::{G, N},
+        curve::{Secp256k1, ToyCurve13, ToyCurve223},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_table_matches_double_and_add() {
+        let table = Secp256k1::generator_table();
+        for k in [
+            Integer::from(1),
+            Integer::from(16),
+            Integer::from(0x8CEF_7310_u32),
+            N.clone() - 1,
+            N.clone() + 5,
+            Integer::from_str_radix("112233445566778899112233445566778899", 10).unwrap(),
+        ] {
+            let expected = ProjectivePoint::from(&*G).mul_ladder(&k).to_affine();
+            assert_eq!(table.mul(&k).to_affine(), expected);
+            assert_eq!(table.mul_secret(&k).to_affine(), expected);
+        }
+        assert_eq!(table.mul(&N).to_affine(), Point::Infinity);
+        assert_eq!(table.mul_secret(&N).to_affine(), Point::Infinity);
+    }
+
+    #[test]
+    fn test_toy_curve_table() {
+        let g = ToyCurve223::generator();
+        let table = GeneratorTable::new(g);
+        let mut expected = Point::Infinity;
+        for k in 0..=42 {
+            assert_eq!(table.mul(&Integer::from(k)).to_affine(), expected);
+            assert_eq!(table.mul_secret(&Integer::from(k)).to_affine(), expected);
+            expected += g.clone();
+        }
+
+        // Order 7, so the single window has entries at infinity
+        let g = ToyCurve13::generator();
+        let table = ToyCurve13::generator_table();
+        let mut expected = Point::Infinity;
+        for k in 0..=15 {
+            assert_eq!(table.mul(&Integer::from(k)).to_affine(), expected);
+            assert_eq!(table.mul_secret(&Integer::from(k)).to_affine(), expected);
+            expected += g.clone();
+        }
+    }
+
+    /// Timing comparison with the generic multiplication paths, run with
+    /// `cargo test --release -- --ignored bench_generator_mul --nocapture`.
+    /// The times are only printed, the asserts just check that all the paths agree
+    #[test]
+    #[ignore]
+    fn bench_generator_mul() {
+        let table = Secp256k1::generator_table();
+        let scalars: Vec<Integer> = (1..=200_u64)
+            .map(|i| (N.clone() - 1) / i + i * 0x8CEF_7310)
+            .collect();
+        let time = |name: &str, mul: &dyn Fn(&Integer) -> Point<Secp256k1>| {
+            let start = Instant::now();
+            let points: Vec<Point<Secp256k1>> = scalars.iter().map(mul).collect();
+            eprintln!(
+                "{name}: {:?} per multiplication",
+                start.elapsed() / scalars.len() as u32
+            );
+            points
+        };
+
+        let ladder_points = time("ladder", &|k| {
+            ProjectivePoint::from(&*G).mul_ladder(k).to_affine()
+        });
+        let secret_points = time("table, constant time", &|k| table.mul_secret(k).to_affine());
+        let wnaf_points = time("wNAF with GLV", &|k| crate::wnaf::mul(k, &*G).to_affine());
+        let table_points = time("table, variable time", &|k| table.mul(k).to_affine());
+
+        assert_eq!(secret_points, ladder_points);
+        assert_eq!(wnaf_points, ladder_points);
+        assert_eq!(table_points, ladder_points);
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(0, 0));
+        assert!(ct_eq(15, 15));
+        assert!(!ct_eq(1, 0));
+        assert!(!ct_eq(0, 15));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    bigint::{Complete, Integer},
+    curve::Curve,
+    field_element::FieldElement,
+    point::Point,
+};
+
+/// Efficiently computable endomorphism (x, y) -> (beta * x, y), which acts on the points of the
+/// curve as multiplication by lambda. It lets a scalar be split in two halves of about half the
+/// bits (Gallant-Lambert-Vanstone), so multiplications need half the doublings.
+/// Only meaningful on curves where every point has order n, i.e. cofactor 1
+pub struct Endomorphism<C: Curve> {
+    beta: FieldElement<C::Field>,
+    lambda: Integer,
+    // Short basis (a1, b1), (a2, b2) of the lattice of (x, y) with x + y * lambda = 0 (mod n)
+    a1: Integer,
+    b1: Integer,
+    a2: Integer,
+    b2: Integer,
+}
+
+impl<C: Curve> Endomorphism<C> {
+    pub fn new(
+        beta: FieldElement<C::Field>,
+        lambda: Integer,
+        (a1, b1): (Integer, Integer),
+        (a2, b2): (Integer, Integer),
+    ) -> Self {
+        Endomorphism {
+            beta,
+            lambda,
+            a1,
+            b1,
+            a2,
+            b2,
+        }
+    }
+
+    pub fn beta(&self) -> &FieldElement<C::Field> {
+        &self.beta
+    }
+
+    pub fn lambda(&self) -> &Integer {
+        &self.lambda
+    }
+
+    /// Returns lambda * point
+    pub fn apply(&self, point: &Point<C>) -> Point<C> {
+        match point {
+            Point::Point(x, y) => Point::Point(&self.beta * x, y.clone()),
+            Point::Infinity => Point::Infinity,
+        }
+    }
+
+    /// Splits k into (k1, k2) with k = k1 + k2 * lambda (mod n), where k1 and k2 may be negative
+    /// and are about half the size of n
+    pub fn decompose(&self, k: &Integer) -> (Integer, Integer) {
+        let n = C::order();
+        let (_, k) = k.div_rem_euc_ref(n).complete();
+        // c1 = round(b2 * k / n), c2 = round(-b1 * k / n), both numerators are non-negative
+        let c1 = (Integer::from(2) * &self.b2 * &k + n) / (Integer::from(2) * n);
+        let c2 = (Integer::from(-2) * &self.b1 * &k + n) / (Integer::from(2) * n);
+        let k1 = k - c1.clone() * &self.a1 - c2.clone() * &self.a2;
+        let k2 = Integer::from(-1) * c1 * &self.b1 - c2 * &self.b2;
+        (k1, k2)
+    }
+}
+
+#[cfg(test)]
+mod glv_tests {
+    use crate::{
+        bigint::Order,
+        constants::{G, N},
+        curve::Secp256k1,
+    };
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    #[test]
+    fn test_endomorphism_is_lambda_multiplication() {
+        let endomorphism = Secp256k1::endomorphism().unwrap();
+        assert_eq!(endomorphism.apply(&G), &*G * endomorphism.lambda());
+        let point = &*G * &Integer::from(0x8CEF_7310_u32);
+        assert_eq!(endomorphism.apply(&point), &point * endomorphism.lambda());
+    }
+
+    #[test]
+    fn test_decompose() {
+        let endomorphism = Secp256k1::endomorphism().unwrap();
+        let mut scalars = vec![
+            Integer::from(0),
+            Integer::from(1),
+            N.clone() - 1,
+            endomorphism.lambda().clone(),
+        ];
+        for _ in 0..100 {
+            let bytes: [u8; 32] = thread_rng().gen();
+            scalars.push(Integer::from_digits(&bytes, Order::Msf));
+        }
+        for k in scalars {
+            let (k1, k2) = endomorphism.decompose(&k);
+            assert!(k1.clone().abs().significant_bits() <= 129);
+            assert!(k2.clone().abs().significant_bits() <= 129);
+            let (_, difference) = (k1 + k2 * endomorphism.lambda() - &k)
+                .div_rem_euc_ref(&N)
+                .complete();
+            assert_eq!(difference, 0);
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bigint::{Integer, Order},
+    errors::BitcoinError::{self, ExpandLengthTooLarge},
+    field_element::FieldElement,
+    point::Point,
+};
+
+// Hashing to secp256k1 as in RFC 9380, suites secp256k1_XMD:SHA-256_SSWU_RO_ and _NU_.
+// The simplified SWU map needs a and b non-zero, so it maps to the curve
+// y**2 = x**3 + A' * x + B', which is 3-isogenous to secp256k1, and then goes through the isogeny
+
+/// Bytes taken from expand_message_xmd per field element, ceil((ceil(log2(p)) + 128) / 8)
+const L: usize = 48;
+/// SHA-256 output and block sizes
+const B_IN_BYTES: usize = 32;
+const S_IN_BYTES: usize = 64;
+
+lazy_static! {
+    static ref ISO_A: FieldElement =
+        felt_hex("3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533");
+    static ref ISO_B: FieldElement = FieldElement::new(Integer::from(1771));
+    static ref Z: FieldElement = FieldElement::new(Integer::from(-11));
+    // Coefficients of the isogeny map, lowest degree first, RFC 9380 appendix E.1
+    static ref X_NUM: [FieldElement; 4] = [
+        felt_hex("8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7"),
+        felt_hex("07d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581"),
+        felt_hex("534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262"),
+        felt_hex("8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c"),
+    ];
+    static ref X_DEN: [FieldElement; 3] = [
+        felt_hex("d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b"),
+        felt_hex("edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14"),
+        FieldElement::new(Integer::from(1)),
+    ];
+    static ref Y_NUM: [FieldElement; 4] = [
+        felt_hex("4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c"),
+        felt_hex("c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3"),
+        felt_hex("29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931"),
+        felt_hex("2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84"),
+    ];
+    static ref Y_DEN: [FieldElement; 4] = [
+        felt_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b"),
+        felt_hex("7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573"),
+        felt_hex("6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f"),
+        FieldElement::new(Integer::from(1)),
+    ];
+}
+
+fn felt_hex(digits: &str) -> FieldElement {
+    FieldElement::new(Integer::from_str_radix(digits, 16).unwrap())
+}
+
+/// secp256k1_XMD:SHA-256_SSWU_RO_, the random oracle encoding: the output is uniformly
+/// distributed over the curve and nobody knows its discrete logarithm
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Point {
+    let u = hash_to_field(msg, dst, 2).unwrap();
+    let q0 = iso_map(map_to_curve_simple_swu(&u[0]));
+    let q1 = iso_map(map_to_curve_simple_swu(&u[1]));
+    // The cofactor is 1, so there is nothing to clear
+    q0 + q1
+}
+
+/// secp256k1_XMD:SHA-256_SSWU_NU_, the nonuniform encoding. Cheaper than `hash_to_curve`, but
+/// it only reaches about half of the points, which not every protocol can afford
+pub fn encode_to_curve(msg: &[u8], dst: &[u8]) -> Point {
+    let u = hash_to_field(msg, dst, 1).unwrap();
+    iso_map(map_to_curve_simple_swu(&u[0]))
+}
+
+/// `count` field elements from the message, reducing 48 bytes of expand_message_xmd each
+pub fn hash_to_field(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+) -> Result<Vec<FieldElement>, BitcoinError> {
+    let uniform_bytes = expand_message_xmd(msg, dst, count * L)?;
+    Ok(uniform_bytes
+        .chunks(L)
+        .map(|chunk| FieldElement::new(Integer::from_digits(chunk, Order::Msf)))
+        .collect())
+}
+
+/// expand_message_xmd with SHA-256 (RFC 9380 section 5.3.1). Domain separation tags longer
+/// than 255 bytes are hashed first, as the RFC requires
+pub fn expand_message_xmd(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, BitcoinError> {
+    // At most 255 blocks, which also keeps the length within the two bytes it's encoded in
+    if len_in_bytes > 255 * B_IN_BYTES {
+        return Err(ExpandLengthTooLarge(len_in_bytes));
+    }
+    let dst = if dst.len() > 255 {
+        Sha256::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize()
+            .to_vec()
+    } else {
+        dst.to_vec()
+    };
+    let mut dst_prime = dst;
+    dst_prime.push(dst_prime.len() as u8);
+
+    let b_0 = Sha256::new()
+        .chain_update([0; S_IN_BYTES])
+        .chain_update(msg)
+        .chain_update((len_in_bytes as u16).to_be_bytes())
+        .chain_update([0])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut b_i = Sha256::new()
+        .chain_update(b_0)
+        .chain_update([1])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut uniform_bytes = b_i.to_vec();
+    let mut i: u8 = 1;
+    while uniform_bytes.len() < len_in_bytes {
+        i += 1;
+        let mut xored = b_0;
+        for (byte, previous) in xored.iter_mut().zip(b_i) {
+            *byte ^= previous;
+        }
+        b_i = Sha256::new()
+            .chain_update(xored)
+            .chain_update([i])
+            .chain_update(&dst_prime)
+            .finalize();
+        uniform_bytes.extend(b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    Ok(uniform_bytes)
+}
+
+/// Simplified SWU map to y**2 = x**3 + A' * x + B' (RFC 9380 section 6.6.2)
+fn map_to_curve_simple_swu(u: &FieldElement) -> (FieldElement, FieldElement) {
+    let one = FieldElement::new(Integer::from(1));
+    let u2 = u * u;
+    let z_u2 = &*Z * &u2;
+    let tv1 = &z_u2 * &z_u2 + z_u2.clone();
+    // inv0, 1 / 0 is taken as 0
+    let x1 = match one.checked_div(&tv1) {
+        Ok(tv1_inv) => {
+            (FieldElement::new(Integer::from(0)) - ISO_B.clone()) / ISO_A.clone() * (one + tv1_inv)
+        }
+        Err(_) => &*ISO_B / &(&*Z * &*ISO_A),
+    };
+    let x2 = &z_u2 * &x1;
+    let (x, y) = match iso_curve_rhs(&x1).sqrt() {
+        Some(y1) => (x1, y1),
+        // If g(x1) isn't a square, g(x2) = Z**3 * u**6 * g(x1) is, as Z isn't a square
+        None => {
+            let y2 = iso_curve_rhs(&x2).sqrt().unwrap();
+            (x2, y2)
+        }
+    };
+    // sgn0, the parity of y must match the parity of u
+    if u.num.is_odd() != y.num.is_odd() {
+        return (x, FieldElement::new(Integer::from(0)) - y);
+    }
+    (x, y)
+}
+
+/// x**3 + A' * x + B'
+fn iso_curve_rhs(x: &FieldElement) -> FieldElement {
+    x * &(x * x) + &*ISO_A * x + ISO_B.clone()
+}
+
+/// The 3-isogeny from the SWU curve to secp256k1
+fn iso_map((x, y): (FieldElement, FieldElement)) -> Point {
+    let x_num = evaluate(&X_NUM[..], &x);
+    let y_num = evaluate(&Y_NUM[..], &x);
+    // The denominators only vanish at the kernel of the isogeny, which the SWU map never outputs
+    match (
+        x_num.checked_div(&evaluate(&X_DEN[..], &x)),
+        y_num.checked_div(&evaluate(&Y_DEN[..], &x)),
+    ) {
+        (Ok(x), Ok(y_ratio)) => Point::Point(x, y * y_ratio),
+        _ => Point::Infinity,
+    }
+}
+
+/// Polynomial with the given coefficients, lowest degree first, at x (Horner's rule)
+fn evaluate(coefficients: &[FieldElement], x: &FieldElement) -> FieldElement {
+    coefficients.iter().rev().fold(
+        FieldElement::new(Integer::from(0)),
+        |result, coefficient| result * x.clone() + coefficient.clone(),
+    )
+}
+
+#[cfg(test)]
+mod hash_to_curve_tests {
+    use crate::utils::encode_hex;
+
+    use super::*;
+
+    const RO_DST: &[u8] = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+    const NU_DST: &[u8] = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_NU_";
+
+    fn messages() -> Vec<Vec<u8>> {
+        vec![
+            b"".to_vec(),
+            b"abc".to_vec(),
+            b"abcdef0123456789".to_vec(),
+            [b"q128_".to_vec(), vec![b'q'; 128]].concat(),
+            [b"a512_".to_vec(), vec![b'a'; 512]].concat(),
+        ]
+    }
+
+    fn point(x: &str, y: &str) -> Point {
+        Point::new_point(felt_hex(x), felt_hex(y)).unwrap()
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380 appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            encode_hex(&expand_message_xmd(b"", dst, 0x20).unwrap()),
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+        );
+        assert_eq!(
+            encode_hex(&expand_message_xmd(b"abc", dst, 0x20).unwrap()),
+            "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+        );
+        assert_eq!(
+            encode_hex(&expand_message_xmd(b"", dst, 0x80).unwrap()),
+            "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe\
+             e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18\
+             eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc\
+             c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"
+        );
+        assert_eq!(
+            expand_message_xmd(b"", dst, 256 * 32),
+            Err(ExpandLengthTooLarge(256 * 32))
+        );
+    }
+
+    #[test]
+    fn test_hash_to_curve_vectors() {
+        // RFC 9380 appendix J.8.1
+        let expected = [
+            point(
+                "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+            ),
+            point(
+                "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+            ),
+            point(
+                "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+                "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+            ),
+            point(
+                "e2167bc785333a37aa562f021f1e881defb853839babf52a7f72b102e41890e9",
+                "f2401dd95cc35867ffed4f367cd564763719fbc6a53e969fb8496a1e6685d873",
+            ),
+            point(
+                "e3c8d35aaaf0b9b647e88a0a0a7ee5d5bed5ad38238152e4e6fd8c1f8cb7c998",
+                "8446eeb6181bf12f56a9d24e262221cc2f0c4725c7e3803024b5888ee5823aa6",
+            ),
+        ];
+        for (msg, expected) in messages().iter().zip(expected) {
+            assert_eq!(hash_to_curve(msg, RO_DST), expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_to_curve_vectors() {
+        // RFC 9380 appendix J.8.2
+        let expected = [
+            point(
+                "a4792346075feae77ac3b30026f99c1441b4ecf666ded19b7522cf65c4c55c5b",
+                "62c59e2a6aeed1b23be5883e833912b08ba06be7f57c0e9cdc663f31639ff3a7",
+            ),
+            point(
+                "3f3b5842033fff837d504bb4ce2a372bfeadbdbd84a1d2b678b6e1d7ee426b9d",
+                "902910d1fef15d8ae2006fc84f2a5a7bda0e0407dc913062c3a493c4f5d876a5",
+            ),
+            point(
+                "07644fa6281c694709f53bdd21bed94dab995671e4a8cd1904ec4aa50c59bfdf",
+                "c79f8d1dad79b6540426922f7fbc9579c3018dafeffcd4552b1626b506c21e7b",
+            ),
+            point(
+                "b734f05e9b9709ab631d960fa26d669c4aeaea64ae62004b9d34f483aa9acc33",
+                "03fc8a4a5a78632e2eb4d8460d69ff33c1d72574b79a35e402e801f2d0b1d6ee",
+            ),
+            point(
+                "17d22b867658977b5002dbe8d0ee70a8cfddec3eec50fb93f36136070fd9fa6c",
+                "e9178ff02f4dab73480f8dd590328aea99856a7b6cc8e5a6cdf289ecc2a51718",
+            ),
+        ];
+        for (msg, expected) in messages().iter().zip(expected) {
+            assert_eq!(encode_to_curve(msg, NU_DST), expected);
+        }
+    }
+
+    #[test]
+    fn test_iso_map_is_homomorphism() {
+        let u: Vec<FieldElement> = (1..5)
+            .map(|i| FieldElement::new(Integer::from(i)))
+            .collect();
+        let p = map_to_curve_simple_swu(&u[0]);
+        let q = map_to_curve_simple_swu(&u[1]);
+        // Sum on the SWU curve with the affine formulas
+        let slope = (&q.1 - &p.1) / (&q.0 - &p.0);
+        let x3 = &slope * &slope - p.0.clone() - q.0.clone();
+        let y3 = slope * (&p.0 - &x3) - p.1.clone();
+        assert_eq!(iso_curve_rhs(&x3), &y3 * &y3);
+        assert_eq!(iso_map((x3, y3)), iso_map(p) + iso_map(q));
+    }
+
+    #[test]
+    fn test_long_dst() {
+        let long_dst = vec![b'x'; 300];
+        let hashed_dst = Sha256::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(&long_dst)
+            .finalize();
+        assert_eq!(
+            hash_to_curve(b"abc", &long_dst),
+            hash_to_curve(b"abc", &hashed_dst)
+        );
+    }
+}
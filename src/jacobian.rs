@@ -0,0 +1,285 @@
+use std::ops::Add;
+
+use crate::{
+    curve::Curve,
+    field_element::{batch_invert, FieldBackend},
+    point::Point,
+};
+
+/// Point in Jacobian coordinates, (X, Y, Z) represents the affine point (X / Z**2, Y / Z**3).
+/// Doubling and addition don't need any field inversion, only the conversion back to affine does.
+/// Coordinates use the curve's field backend.
+/// Sums and multiples can be accumulated here and converted to affine at the end, many points at
+/// once with `normalize_batch`
+#[derive(Clone, Debug)]
+pub struct JacobianPoint<C: Curve> {
+    x: C::Backend,
+    y: C::Backend,
+    z: C::Backend,
+}
+
+impl<C: Curve> JacobianPoint<C> {
+    pub fn infinity() -> Self {
+        JacobianPoint {
+            x: C::Backend::one(),
+            y: C::Backend::one(),
+            z: C::Backend::zero(),
+        }
+    }
+
+    pub(crate) fn from_affine(x: C::Backend, y: C::Backend) -> Self {
+        JacobianPoint {
+            x,
+            y,
+            z: C::Backend::one(),
+        }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Affine coordinates in the field backend, None for the point at infinity
+    pub(crate) fn to_affine_coordinates(&self) -> Option<(C::Backend, C::Backend)> {
+        if self.is_infinity() {
+            return None;
+        }
+        let z_inv = C::Backend::one() / self.z.clone();
+        let z_inv_2 = z_inv.clone() * z_inv.clone();
+        let x = self.x.clone() * z_inv_2.clone();
+        let y = self.y.clone() * z_inv_2 * z_inv;
+        Some((x, y))
+    }
+
+    pub fn to_affine(&self) -> Point<C> {
+        match self.to_affine_coordinates() {
+            Some((x, y)) => Point::Point(x.into(), y.into()),
+            None => Point::Infinity,
+        }
+    }
+
+    /// Affine coordinates of all the points with a single field inversion, None for the points
+    /// at infinity
+    pub(crate) fn batch_to_affine_coordinates(
+        points: &[Self],
+    ) -> Vec<Option<(C::Backend, C::Backend)>> {
+        let z_values: Vec<C::Backend> = points
+            .iter()
+            .filter(|point| !point.is_infinity())
+            .map(|point| point.z.clone())
+            .collect();
+        // z is only 0 at infinity
+        let mut z_inverses = batch_invert(&z_values).unwrap().into_iter();
+        points
+            .iter()
+            .map(|point| {
+                if point.is_infinity() {
+                    return None;
+                }
+                let z_inv = z_inverses.next().unwrap();
+                let z_inv_2 = z_inv.clone() * z_inv.clone();
+                let x = point.x.clone() * z_inv_2.clone();
+                let y = point.y.clone() * z_inv_2 * z_inv;
+                Some((x, y))
+            })
+            .collect()
+    }
+
+    /// Affine form of all the points with a single field inversion, instead of one per point
+    pub fn normalize_batch(points: &[Self]) -> Vec<Point<C>> {
+        Self::batch_to_affine_coordinates(points)
+            .into_iter()
+            .map(|coordinates| match coordinates {
+                Some((x, y)) => Point::Point(x.into(), y.into()),
+                None => Point::Infinity,
+            })
+            .collect()
+    }
+
+    pub(crate) fn neg(&self) -> Self {
+        JacobianPoint {
+            x: self.x.clone(),
+            y: C::Backend::zero() - self.y.clone(),
+            z: self.z.clone(),
+        }
+    }
+
+    /// (beta * X, Y, Z), the image under the endomorphism (x, y) -> (beta * x, y)
+    pub(crate) fn endomorphism(&self, beta: &C::Backend) -> Self {
+        JacobianPoint {
+            x: beta.clone() * self.x.clone(),
+            y: self.y.clone(),
+            z: self.z.clone(),
+        }
+    }
+
+    /// dbl-2007-bl, valid for any a
+    pub fn double(&self) -> Self {
+        if self.is_infinity() || self.y.is_zero() {
+            return Self::infinity();
+        }
+        let xx = self.x.clone() * self.x.clone();
+        let yy = self.y.clone() * self.y.clone();
+        let yyyy = yy.clone() * yy.clone();
+        let x_yy = self.x.clone() + yy;
+        let s = double(x_yy.clone() * x_yy - xx.clone() - yyyy.clone());
+        let mut m = double(xx.clone()) + xx;
+        if !C::a().is_zero() {
+            let zz = self.z.clone() * self.z.clone();
+            m = m + C::Backend::from(C::a().clone()) * zz.clone() * zz;
+        }
+        let x3 = m.clone() * m.clone() - double(s.clone());
+        let y3 = m * (s - x3.clone()) - double(double(double(yyyy)));
+        let z3 = double(self.y.clone() * self.z.clone());
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Mixed addition (add-1998-cmo-2 with Z2 = 1), `other` must have Z = 1, like points built
+    /// from affine ones
+    pub(crate) fn add_mixed(&self, other: &JacobianPoint<C>) -> Self {
+        if other.is_infinity() {
+            return self.clone();
+        }
+        if self.is_infinity() {
+            return other.clone();
+        }
+        debug_assert!(other.z == C::Backend::one());
+        let z1z1 = self.z.clone() * self.z.clone();
+        let u2 = other.x.clone() * z1z1.clone();
+        let s2 = other.y.clone() * self.z.clone() * z1z1;
+        self.finish_add(self.x.clone(), self.y.clone(), u2, s2, self.z.clone())
+    }
+
+    /// Shared tail of the addition formulas, given U1, S1, U2, S2 and Z1 * Z2 (add-1998-cmo-2)
+    fn finish_add(
+        &self,
+        u1: C::Backend,
+        s1: C::Backend,
+        u2: C::Backend,
+        s2: C::Backend,
+        z1z2: C::Backend,
+    ) -> Self {
+        if u1 == u2 {
+            if s1 == s2 {
+                return self.double();
+            }
+            return Self::infinity();
+        }
+        let h = u2 - u1.clone();
+        let r = s2 - s1.clone();
+        let hh = h.clone() * h.clone();
+        let hhh = h.clone() * hh.clone();
+        let v = u1 * hh;
+        let x3 = r.clone() * r.clone() - hhh.clone() - double(v.clone());
+        let y3 = r * (v - x3.clone()) - s1 * hhh;
+        let z3 = z1z2 * h;
+        JacobianPoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+fn double<E: Clone + Add<Output = E>>(element: E) -> E {
+    element.clone() + element
+}
+
+impl<C: Curve> From<&Point<C>> for JacobianPoint<C> {
+    fn from(point: &Point<C>) -> Self {
+        match point {
+            Point::Infinity => JacobianPoint::infinity(),
+            Point::Point(x, y) => JacobianPoint {
+                x: C::Backend::from(x.clone()),
+                y: C::Backend::from(y.clone()),
+                z: C::Backend::one(),
+            },
+        }
+    }
+}
+
+impl<C: Curve> Add<&JacobianPoint<C>> for &JacobianPoint<C> {
+    type Output = JacobianPoint<C>;
+
+    fn add(self, other: &JacobianPoint<C>) -> JacobianPoint<C> {
+        if self.is_infinity() {
+            return other.clone();
+        }
+        if other.is_infinity() {
+            return self.clone();
+        }
+        let z1z1 = self.z.clone() * self.z.clone();
+        let z2z2 = other.z.clone() * other.z.clone();
+        let u1 = self.x.clone() * z2z2.clone();
+        let u2 = other.x.clone() * z1z1.clone();
+        let s1 = self.y.clone() * other.z.clone() * z2z2;
+        let s2 = other.y.clone() * self.z.clone() * z1z1;
+        self.finish_add(u1, s1, u2, s2, self.z.clone() * other.z.clone())
+    }
+}
+
+#[cfg(test)]
+mod jacobian_tests {
+    use crate::{
+        bigint::Integer,
+        constants::G,
+        curve::{Secp256k1, ToyCurve223},
+        felt,
+        field_element::{FieldElement, F223},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_double_and_add_match_affine() {
+        let g = JacobianPoint::from(&*G);
+        let two_g = g.double();
+        assert_eq!(two_g.to_affine(), G.clone() + G.clone());
+        assert_eq!(
+            two_g.add_mixed(&g).to_affine(),
+            G.clone() + G.clone() + G.clone()
+        );
+        assert_eq!(
+            (&two_g + &two_g.double()).to_affine(),
+            &*G * &Integer::from(6)
+        );
+        assert_eq!((&two_g + &two_g).to_affine(), &*G * &Integer::from(4));
+        let minus_g = match &*G {
+            Point::Point(x, y) => Point::<Secp256k1>::Point(x.clone(), felt!(0) - y.clone()),
+            Point::Infinity => unreachable!(),
+        };
+        assert!(g.add_mixed(&JacobianPoint::from(&minus_g)).is_infinity());
+        assert!(JacobianPoint::<Secp256k1>::infinity().to_affine() == Point::Infinity);
+    }
+
+    #[test]
+    fn test_toy_curve_multiples() {
+        let g = ToyCurve223::generator();
+        let g_jacobian = JacobianPoint::from(g);
+        let mut affine = Point::Infinity;
+        let mut jacobian = JacobianPoint::<ToyCurve223>::infinity();
+        let mut points = vec![jacobian.clone()];
+        let mut expected = vec![Point::Infinity];
+        for _ in 0..22 {
+            affine += g.clone();
+            jacobian = jacobian.add_mixed(&g_jacobian);
+            assert_eq!(jacobian.to_affine(), affine);
+            assert_eq!(
+                jacobian.double().to_affine(),
+                affine.clone() + affine.clone()
+            );
+            points.push(jacobian.clone());
+            expected.push(affine.clone());
+        }
+        assert_eq!(JacobianPoint::normalize_batch(&points), expected);
+        let p = Point::<ToyCurve223>::new_point(felt!(192, F223), felt!(105, F223)).unwrap();
+        assert_eq!(
+            (&JacobianPoint::from(&p) + &jacobian).to_affine(),
+            p + affine
+        );
+    }
+}
@@ -1,10 +1,24 @@
+pub mod address;
+pub mod bigint;
 mod constants;
+pub mod curve;
 pub mod errors;
-mod field_element;
-mod point;
+pub mod field_element;
+pub mod generator_table;
+pub mod glv;
+pub mod hash_to_curve;
+pub mod jacobian;
+mod msm;
+pub mod point;
 pub mod private_key;
+mod projective;
+pub mod scalar;
+pub mod secp256k1_field;
+#[cfg(feature = "serde")]
+mod serialization;
 pub mod signature;
 pub mod utils;
+mod wnaf;
 fn main() {
     println!("Hello, world!");
 }
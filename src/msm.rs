@@ -0,0 +1,187 @@
+use crate::{
+    bigint::{Complete, Integer},
+    curve::Curve,
+    jacobian::JacobianPoint,
+    point::Point,
+    wnaf,
+};
+
+/// From this many terms on, Pippenger's bucket method is faster than Straus
+const PIPPENGER_THRESHOLD: usize = 32;
+
+/// Sum of k_i * P_i, with Straus for few terms and Pippenger for many. Variable time
+pub(crate) fn multi_scalar_mul<C: Curve>(terms: &[(&Integer, &Point<C>)]) -> Point<C> {
+    if terms.len() < PIPPENGER_THRESHOLD {
+        wnaf::straus(terms).to_affine()
+    } else {
+        pippenger(terms).to_affine()
+    }
+}
+
+/// Bucket method: each window of c bits of all the scalars is handled by adding every point to
+/// the bucket of its digit, then summing j * bucket_j with two running sums. The cost per term
+/// is about one addition per window instead of a full scalar multiplication
+fn pippenger<C: Curve>(terms: &[(&Integer, &Point<C>)]) -> JacobianPoint<C> {
+    let (scalars, points) = prepare(terms);
+    let c = window_bits(scalars.len());
+    let bits = scalars
+        .iter()
+        .map(|k| k.significant_bits() as usize)
+        .max()
+        .unwrap_or(0);
+
+    let mut result = JacobianPoint::infinity();
+    for window in (0..bits / c + 1).rev() {
+        for _ in 0..c {
+            result = result.double();
+        }
+        let mut buckets = vec![JacobianPoint::infinity(); (1 << c) - 1];
+        for (k, point) in scalars.iter().zip(&points) {
+            let digit = window_digit(k, window * c, c);
+            if digit != 0 {
+                buckets[digit - 1] = buckets[digit - 1].add_mixed(point);
+            }
+        }
+        // running is the sum of the buckets from j up, so adding it once per j gives
+        // the sum of j * bucket_j
+        let mut running = JacobianPoint::infinity();
+        let mut window_sum = JacobianPoint::infinity();
+        for bucket in buckets.iter().rev() {
+            running = &running + bucket;
+            window_sum = &window_sum + &running;
+        }
+        result = &result + &window_sum;
+    }
+    result
+}
+
+/// Non-negative scalars with their points in Jacobian form with Z = 1, skipping the points at
+/// infinity. With an endomorphism every term is split in two of half the bits
+fn prepare<C: Curve>(terms: &[(&Integer, &Point<C>)]) -> (Vec<Integer>, Vec<JacobianPoint<C>>) {
+    let mut scalars = Vec::with_capacity(terms.len() * 2);
+    let mut points = Vec::with_capacity(terms.len() * 2);
+    let mut push = |k: Integer, point: JacobianPoint<C>| {
+        if k < 0 {
+            scalars.push(-k);
+            points.push(point.neg());
+        } else {
+            scalars.push(k);
+            points.push(point);
+        }
+    };
+    match C::endomorphism() {
+        Some(endomorphism) => {
+            let beta = C::Backend::from(endomorphism.beta().clone());
+            for (k, point) in terms.iter().filter(|(_, point)| !point.is_infinity()) {
+                let (k1, k2) = endomorphism.decompose(k);
+                let point = JacobianPoint::from(*point);
+                push(k2, point.endomorphism(&beta));
+                push(k1, point);
+            }
+        }
+        None => {
+            // The order of any point divides the number of points
+            let group_order = C::order().clone() * C::cofactor();
+            for (k, point) in terms.iter().filter(|(_, point)| !point.is_infinity()) {
+                let (_, k) = k.div_rem_euc_ref(&group_order).complete();
+                push(k, JacobianPoint::from(*point));
+            }
+        }
+    }
+    (scalars, points)
+}
+
+/// Window size in bits for n terms, growing with log n like in most implementations
+fn window_bits(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        n.ilog2() as usize * 69 / 100 + 2
+    }
+}
+
+/// Bits [start, start + c) of k
+fn window_digit(k: &Integer, start: usize, c: usize) -> usize {
+    (0..c).fold(0, |digit, i| {
+        digit | (k.get_bit((start + i) as u32) as usize) << i
+    })
+}
+
+#[cfg(test)]
+mod msm_tests {
+    use crate::{
+        constants::{G, N},
+        curve::{Secp256k1, ToyCurve223},
+        felt,
+        field_element::{FieldElement, F223},
+    };
+
+    use super::*;
+
+    fn secp256k1_terms(n: usize) -> (Vec<Integer>, Vec<Point>) {
+        let scalars: Vec<Integer> = (0..n)
+            .map(|i| (N.clone() - 1) / (i + 2) * (i as i32 % 3 - 1) + i)
+            .collect();
+        let mut points: Vec<Point> = (1..=n)
+            .map(|i| &*G * &Integer::from(i * 0x8CEF_7310))
+            .collect();
+        if n > 2 {
+            points[1] = G.clone();
+            points[2] = Point::Infinity;
+        }
+        (scalars, points)
+    }
+
+    fn naive<C: Curve>(scalars: &[Integer], points: &[Point<C>]) -> Point<C> {
+        scalars.iter().zip(points).map(|(k, point)| point * k).sum()
+    }
+
+    #[test]
+    fn test_straus_and_pippenger() {
+        for n in [0, 1, 2, 5, 40] {
+            let (scalars, points) = secp256k1_terms(n);
+            let terms: Vec<(&Integer, &Point)> = scalars.iter().zip(&points).collect();
+            let expected = naive(&scalars, &points);
+            assert_eq!(wnaf::straus(&terms).to_affine(), expected);
+            assert_eq!(pippenger(&terms).to_affine(), expected);
+            assert_eq!(multi_scalar_mul(&terms), expected);
+        }
+    }
+
+    #[test]
+    fn test_toy_curve_multi_scalar_mul() {
+        // Points outside the subgroup of the generator, so scalars can't be reduced modulo
+        // its order
+        let mut points = Vec::new();
+        for x in 0..223 {
+            for y in 0..223 {
+                if let Ok(point) = Point::<ToyCurve223>::new_point(felt!(x, F223), felt!(y, F223)) {
+                    points.push(point);
+                }
+            }
+        }
+        points.push(ToyCurve223::generator().clone());
+        let scalars: Vec<Integer> = (0..points.len())
+            .map(|i| Integer::from(i as i32 * 37 % 500 - 250))
+            .collect();
+        let terms: Vec<(&Integer, &Point<ToyCurve223>)> = scalars.iter().zip(&points).collect();
+        let expected = naive(&scalars, &points);
+        assert_eq!(wnaf::straus(&terms).to_affine(), expected);
+        assert_eq!(pippenger(&terms).to_affine(), expected);
+        for n in [1, 3, 17, 64] {
+            assert_eq!(
+                pippenger(&terms[..n]).to_affine(),
+                naive(&scalars[..n], &points[..n])
+            );
+        }
+    }
+
+    #[test]
+    fn test_opposite_terms_cancel() {
+        let (scalars, points) = secp256k1_terms(200);
+        let negated: Vec<Integer> = scalars.iter().map(|k| (-k).complete()).collect();
+        let mut terms: Vec<(&Integer, &Point<Secp256k1>)> = scalars.iter().zip(&points).collect();
+        terms.extend(negated.iter().zip(&points));
+        assert_eq!(multi_scalar_mul(&terms), Point::Infinity);
+    }
+}
@@ -1,107 +1,373 @@
-use std::ops::{Add, Mul};
+use std::{
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
 
 use crate::{
-    constants::{A, B},
-    errors::BitcoinError::{self, PointNotInTheCurve},
+    address::{p2pkh_address, Network},
+    bigint::{Complete, Integer, Order},
+    curve::{Curve, Secp256k1},
+    errors::BitcoinError::{self, InvalidSecLength, InvalidSecPrefix, PointNotInTheCurve},
     felt,
-    field_element::FieldElement,
+    field_element::{FieldElement, PrimeField},
+    jacobian::JacobianPoint,
+    msm,
+    projective::ProjectivePoint,
+    utils::{hash160, to_fixed_bytes},
+    wnaf,
 };
-use rug::Integer;
-
-// Point of y**2 = x**2 + a*x + b eliptic curve
+// Point of y**2 = x**3 + a*x + b eliptic curve
 #[derive(Clone, Debug, PartialEq)]
-pub enum Point {
-    Point(FieldElement, FieldElement),
+pub enum Point<C: Curve = Secp256k1> {
+    Point(FieldElement<C::Field>, FieldElement<C::Field>),
     Infinity,
 }
 
-impl Point {
-    pub fn new_point(x: FieldElement, y: FieldElement) -> Result<Self, BitcoinError> {
-        if y.pow(&felt!(2)) != x.pow(&felt!(3)) + A.clone() * x.clone() + B.clone() {
+impl<C: Curve> Point<C> {
+    pub fn new_point(
+        x: FieldElement<C::Field>,
+        y: FieldElement<C::Field>,
+    ) -> Result<Self, BitcoinError> {
+        if y.pow(&felt!(2, C::Field))
+            != x.pow(&felt!(3, C::Field)) + C::a().clone() * x.clone() + C::b().clone()
+        {
             return Err(PointNotInTheCurve);
         }
 
         Ok(Point::Point(x, y))
     }
+    /// Point with the given x coordinate and parity of y, as in compressed public keys
+    pub fn from_x(x: FieldElement<C::Field>, y_is_odd: bool) -> Result<Self, BitcoinError> {
+        let alpha = x.pow(&felt!(3, C::Field)) + C::a().clone() * x.clone() + C::b().clone();
+        let beta = alpha.sqrt().ok_or(PointNotInTheCurve)?;
+        let y = if beta.num.is_odd() == y_is_odd {
+            beta
+        } else {
+            felt!(0, C::Field) - beta
+        };
+        // y = 0 is its own negation, so it can't have the other parity
+        if y.num.is_odd() != y_is_odd {
+            return Err(PointNotInTheCurve);
+        }
+        Ok(Point::Point(x, y))
+    }
+
+    /// Point with the given x coordinate and even y, as in BIP340 x-only public keys
+    pub fn lift_x(x: FieldElement<C::Field>) -> Result<Self, BitcoinError> {
+        Point::from_x(x, false)
+    }
+
+    /// SEC encoding: 0x04 followed by x and y, or when compressed 0x02 or 0x03 for even or odd y
+    /// followed by x. The point at infinity is the single byte 0x00
+    pub fn sec(&self, compressed: bool) -> Vec<u8> {
+        let width = Self::coordinate_width();
+        match self {
+            Point::Infinity => vec![0],
+            Point::Point(x, y) if compressed => {
+                let mut bytes = vec![if y.num.is_odd() { 3 } else { 2 }];
+                bytes.extend(to_fixed_bytes(&x.num, width));
+                bytes
+            }
+            Point::Point(x, y) => {
+                let mut bytes = vec![4];
+                bytes.extend(to_fixed_bytes(&x.num, width));
+                bytes.extend(to_fixed_bytes(&y.num, width));
+                bytes
+            }
+        }
+    }
+
+    /// Parses compressed and uncompressed SEC, and 0x00 for the point at infinity.
+    /// Compressed points get their y from a square root
+    pub fn parse_sec(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        let width = Self::coordinate_width();
+        let coordinate =
+            |bytes: &[u8]| FieldElement::try_from(Integer::from_digits(bytes, Order::Msf));
+        match bytes.first() {
+            Some(0) if bytes.len() == 1 => Ok(Point::Infinity),
+            Some(prefix @ (2 | 3)) if bytes.len() == 1 + width => {
+                Point::from_x(coordinate(&bytes[1..])?, *prefix == 3)
+            }
+            Some(4) if bytes.len() == 1 + 2 * width => Point::new_point(
+                coordinate(&bytes[1..1 + width])?,
+                coordinate(&bytes[1 + width..])?,
+            ),
+            Some(0 | 2 | 3 | 4) | None => Err(InvalidSecLength(bytes.len())),
+            Some(prefix) => Err(InvalidSecPrefix(*prefix)),
+        }
+    }
+
+    /// Bytes taken by each coordinate in SEC
+    fn coordinate_width() -> usize {
+        C::Field::prime().significant_digits::<u8>()
+    }
+
     pub fn point_from_hex(x: String, y: String) -> Result<Self, BitcoinError> {
-        Point::new_point(FieldElement::from_hex(x), FieldElement::from_hex(y))
+        Point::new_point(FieldElement::from_hex(x)?, FieldElement::from_hex(y)?)
     }
     pub fn new_infinity() -> Self {
         Point::Infinity
     }
 
-    pub fn get_x(&self) -> &FieldElement {
+    /// Constant time scalar multiplication, for secret scalars like private keys and nonces.
+    /// The `*` operator is faster but its running time depends on the scalar, so it should only
+    /// be used with public data
+    pub fn mul_secret(&self, scalar: &Integer) -> Point<C> {
+        self.mul_secret_projective(scalar).to_affine()
+    }
+
+    /// `mul_secret` for many scalars, like deriving many public keys. All the results are
+    /// converted to affine with a single field inversion
+    pub fn mul_secret_batch(&self, scalars: &[Integer]) -> Vec<Point<C>> {
+        let products: Vec<ProjectivePoint<C>> = scalars
+            .iter()
+            .map(|scalar| self.mul_secret_projective(scalar))
+            .collect();
+        ProjectivePoint::normalize_batch(&products)
+    }
+
+    /// `*` for many scalars, with a single field inversion for all the results. Variable time
+    pub fn mul_batch(&self, scalars: &[Integer]) -> Vec<Point<C>> {
+        let products: Vec<JacobianPoint<C>> = scalars
+            .iter()
+            .map(|scalar| self.mul_jacobian(scalar))
+            .collect();
+        JacobianPoint::normalize_batch(&products)
+    }
+
+    fn mul_secret_projective(&self, scalar: &Integer) -> ProjectivePoint<C> {
+        if self == C::generator() {
+            return C::generator_table().mul_secret(scalar);
+        }
+        // The order of any point divides the number of points, so reducing modulo it keeps the
+        // result and makes negative scalars non-negative
+        let group_order = C::order().clone() * C::cofactor();
+        let (_, scalar) = scalar.div_rem_euc_ref(&group_order).complete();
+        // The ladder can't handle points of order 2, which only exist on curves of even order
+        if matches!(self, Point::Point(_, y) if y.num == 0) {
+            return ProjectivePoint::from(&(self * &scalar));
+        }
+        ProjectivePoint::from(self).mul_ladder(&scalar)
+    }
+
+    fn mul_jacobian(&self, scalar: &Integer) -> JacobianPoint<C> {
+        if self == C::generator() {
+            return C::generator_table().mul(scalar);
+        }
+
+        // wNAF in Jacobian coordinates, so that only the final conversion back to affine
+        // needs an inversion. On secp256k1 the scalar is split in halves with the endomorphism
+        wnaf::mul(scalar, self)
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        matches!(self, Point::Infinity)
+    }
+
+    /// x coordinate, None for the point at infinity
+    pub fn get_x(&self) -> Option<&FieldElement<C::Field>> {
         match self {
-            Point::Point(x, _) => x,
-            _ => panic!(),
+            Point::Point(x, _) => Some(x),
+            Point::Infinity => None,
         }
     }
 
-    /// Returns the slope of the tangent line at a given point
-    pub fn tangent_slope(&self) -> FieldElement {
+    /// y coordinate, None for the point at infinity
+    pub fn get_y(&self) -> Option<&FieldElement<C::Field>> {
         match self {
-            Point::Point(x, y) => {
-                (felt!(3) * x.pow(&felt!(2)) + A.clone()) / (felt!(2) * y.clone())
-            }
-            _ => panic!(),
+            Point::Point(_, y) => Some(y),
+            Point::Infinity => None,
+        }
+    }
+
+    /// Returns u * G + v * point with a single interleaved wNAF loop, which is roughly half the
+    /// cost of two separate multiplications. Variable time, meant for signature verification
+    pub fn double_scalar_mul(u: &Integer, v: &Integer, point: &Point<C>) -> Point<C> {
+        wnaf::double_scalar_mul(u, v, point)
+    }
+
+    /// Returns the sum of k_i * P_i for many (k_i, P_i) terms, much faster than separate
+    /// multiplications. Uses Straus for few terms and Pippenger for many. Variable time
+    pub fn multi_scalar_mul(terms: &[(&Integer, &Point<C>)]) -> Point<C> {
+        msm::multi_scalar_mul(terms)
+    }
+
+    /// Returns the slope of the tangent line at a given point, None for the point at infinity
+    /// and when the tangent is vertical (y = 0)
+    pub fn tangent_slope(&self) -> Option<FieldElement<C::Field>> {
+        match self {
+            Point::Point(_, y) if y.num == Integer::ZERO => None,
+            Point::Point(x, y) => Some(
+                (felt!(3, C::Field) * x.pow(&felt!(2, C::Field)) + C::a().clone())
+                    / (felt!(2, C::Field) * y.clone()),
+            ),
+            Point::Infinity => None,
         }
     }
 
-    /// Returns the slope of the line between two points
-    pub fn slope(&self, other: &Point) -> FieldElement {
+    /// Returns the slope of the line between two points, None if one of them is the point at
+    /// infinity or the line is vertical (same x)
+    pub fn slope(&self, other: &Point<C>) -> Option<FieldElement<C::Field>> {
         match (self, other) {
-            (Point::Point(x1, y1), Point::Point(x2, y2)) => (y2 - y1) / (x2 - x1),
-            _ => panic!(),
+            (Point::Point(x1, _), Point::Point(x2, _)) if x1 == x2 => None,
+            (Point::Point(x1, y1), Point::Point(x2, y2)) => Some((y2 - y1) / (x2 - x1)),
+            _ => None,
         }
     }
 }
 
-impl Add<Point> for Point {
-    type Output = Self;
-    fn add(self, other_point: Point) -> Self {
-        match (&self, &other_point) {
-            (Self::Infinity, _) => other_point,
-            (_, Self::Infinity) => self,
-            (Self::Point(x1, y1), Self::Point(x2, _y2)) => {
-                if self == other_point {
-                    if y1.num == Integer::ZERO {
-                        Point::Infinity
-                    } else {
-                        let slope = self.tangent_slope();
-                        let x3 = slope.pow(&felt!(2)) - felt!(2) * x1.clone();
+impl Point {
+    /// HASH160 of the SEC encoding
+    pub fn hash160(&self, compressed: bool) -> [u8; 20] {
+        hash160(&self.sec(compressed))
+    }
+
+    /// Legacy pay to public key hash address
+    pub fn address(&self, compressed: bool, network: Network) -> String {
+        p2pkh_address(&self.hash160(compressed), network)
+    }
+}
+
+impl<C: Curve> Add<&Point<C>> for &Point<C> {
+    type Output = Point<C>;
+
+    fn add(self, other_point: &Point<C>) -> Point<C> {
+        match (self, other_point) {
+            (Point::Infinity, _) => other_point.clone(),
+            (_, Point::Infinity) => self.clone(),
+            (Point::Point(x1, y1), Point::Point(x2, _y2)) => {
+                let slope = if self == other_point {
+                    self.tangent_slope()
+                } else {
+                    self.slope(other_point)
+                };
+                match slope {
+                    // On the curve whenever both points are
+                    Some(slope) => {
+                        let x3 = slope.pow(&felt!(2, C::Field)) - x1.clone() - x2.clone();
                         let y = slope * (x1 - &x3) - y1.clone();
-                        Point::new_point(x3, y).unwrap()
+                        Point::Point(x3, y)
                     }
-                } else if x1 == x2 {
-                    return Point::Infinity;
-                } else {
-                    let slope = self.slope(&other_point);
-                    let x3 = slope.pow(&felt!(2)) - x1.clone() - x2.clone();
-                    let y = slope * (x1 - &x3) - y1.clone();
-                    return Point::new_point(x3, y).unwrap();
+                    // Vertical line, the points are opposite
+                    None => Point::Infinity,
                 }
             }
         }
     }
 }
 
-impl Mul<&Integer> for &Point {
-    type Output = Point;
+/// Same as `new_point`, checking that (x, y) is on the curve
+impl<C: Curve> TryFrom<(FieldElement<C::Field>, FieldElement<C::Field>)> for Point<C> {
+    type Error = BitcoinError;
 
-    fn mul(self, scalar: &Integer) -> Point {
-        assert!(scalar != &Integer::ZERO, "Cant multiply by 0");
+    fn try_from(
+        (x, y): (FieldElement<C::Field>, FieldElement<C::Field>),
+    ) -> Result<Self, BitcoinError> {
+        Point::new_point(x, y)
+    }
+}
 
-        let mut current = self.clone();
-        let mut result = Point::new_infinity();
-        let mut coef = scalar.clone();
-        while coef != Integer::ZERO {
-            if (&coef & Integer::from(1)) != Integer::ZERO {
-                result = result + current.clone();
-            }
-            current = current.clone() + current.clone();
-            coef >>= 1;
+impl<C: Curve> Add<Point<C>> for Point<C> {
+    type Output = Self;
+
+    fn add(self, other_point: Point<C>) -> Self {
+        &self + &other_point
+    }
+}
+
+impl<C: Curve> AddAssign<&Point<C>> for Point<C> {
+    fn add_assign(&mut self, other_point: &Point<C>) {
+        *self = &*self + other_point;
+    }
+}
+
+impl<C: Curve> AddAssign<Point<C>> for Point<C> {
+    fn add_assign(&mut self, other_point: Point<C>) {
+        *self = &*self + &other_point;
+    }
+}
+
+impl<C: Curve> Neg for &Point<C> {
+    type Output = Point<C>;
+
+    fn neg(self) -> Point<C> {
+        match self {
+            Point::Point(x, y) => Point::Point(x.clone(), felt!(0, C::Field) - y.clone()),
+            Point::Infinity => Point::Infinity,
         }
-        result
+    }
+}
+
+impl<C: Curve> Neg for Point<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        -&self
+    }
+}
+
+impl<C: Curve> Sub<&Point<C>> for &Point<C> {
+    type Output = Point<C>;
+
+    fn sub(self, other_point: &Point<C>) -> Point<C> {
+        self + &-other_point
+    }
+}
+
+impl<C: Curve> Sub<Point<C>> for Point<C> {
+    type Output = Self;
+
+    fn sub(self, other_point: Point<C>) -> Self {
+        &self - &other_point
+    }
+}
+
+impl<C: Curve> SubAssign<&Point<C>> for Point<C> {
+    fn sub_assign(&mut self, other_point: &Point<C>) {
+        *self = &*self - other_point;
+    }
+}
+
+impl<C: Curve> SubAssign<Point<C>> for Point<C> {
+    fn sub_assign(&mut self, other_point: Point<C>) {
+        *self = &*self - &other_point;
+    }
+}
+
+// Sums are accumulated in Jacobian coordinates, with a single inversion at the end
+impl<'a, C: Curve> Sum<&'a Point<C>> for Point<C> {
+    fn sum<I: Iterator<Item = &'a Point<C>>>(iter: I) -> Self {
+        iter.fold(JacobianPoint::infinity(), |sum, point| {
+            &sum + &JacobianPoint::from(point)
+        })
+        .to_affine()
+    }
+}
+
+impl<C: Curve> Sum<Point<C>> for Point<C> {
+    fn sum<I: Iterator<Item = Point<C>>>(iter: I) -> Self {
+        iter.fold(JacobianPoint::infinity(), |sum, point| {
+            &sum + &JacobianPoint::from(&point)
+        })
+        .to_affine()
+    }
+}
+
+/// Variable time multiplication, for public scalars. Any integer works, including 0 and negative
+/// numbers
+impl<C: Curve> Mul<&Integer> for &Point<C> {
+    type Output = Point<C>;
+
+    fn mul(self, scalar: &Integer) -> Point<C> {
+        self.mul_jacobian(scalar).to_affine()
+    }
+}
+
+impl<C: Curve> Mul<&Integer> for Point<C> {
+    type Output = Point<C>;
+
+    fn mul(self, scalar: &Integer) -> Point<C> {
+        &self * scalar
     }
 }
 
@@ -111,7 +377,11 @@ mod point_tests {
 
     use crate::{
         constants::{G, N},
+        curve::{ToyCurve13, ToyCurve223},
+        errors::BitcoinError::FieldElementOutOfRange,
         felt_str,
+        field_element::{F13, F223},
+        utils::{decode_hex, encode_hex},
     };
 
     use super::*;
@@ -119,13 +389,371 @@ mod point_tests {
     #[test]
     fn test_point_sum() {
         assert_eq!(
-            Point::new_point(felt_str!("24398930080362702090489479461985180105578816720137874339735832822062375759460"), felt_str!("54007149621299385755644170254411630654493180623064781550649051280267079509173")).unwrap() + Point::new_point(felt_str!("59864537116326559311346833611109531662167483564301454271272386388321477649300"), felt_str!("75550953355817334437637498591236360658870728572722501436515415412283060275131")).unwrap(),
+            Point::<Secp256k1>::new_point(felt_str!("24398930080362702090489479461985180105578816720137874339735832822062375759460"), felt_str!("54007149621299385755644170254411630654493180623064781550649051280267079509173")).unwrap() + Point::new_point(felt_str!("59864537116326559311346833611109531662167483564301454271272386388321477649300"), felt_str!("75550953355817334437637498591236360658870728572722501436515415412283060275131")).unwrap(),
             Point::new_point(felt_str!("64226399123092535235995845779670929663801771913640521823243056334938852347306"), felt_str!("113687732421995957741893049079948608351368126315416472092696176881410614162809")).unwrap()
 
 
         )
     }
 
+    fn toy_point(x: i32, y: i32) -> Point<ToyCurve223> {
+        Point::new_point(felt!(x, F223), felt!(y, F223)).unwrap()
+    }
+
+    #[test]
+    fn test_toy_curve_points() {
+        assert!(Point::<ToyCurve223>::new_point(felt!(192, F223), felt!(105, F223)).is_ok());
+        assert!(Point::<ToyCurve223>::new_point(felt!(200, F223), felt!(119, F223)).is_err());
+        assert!(Point::<ToyCurve223>::new_point(felt!(42, F223), felt!(99, F223)).is_err());
+    }
+
+    #[test]
+    fn test_toy_curve_sum() {
+        assert_eq!(
+            toy_point(170, 142) + toy_point(60, 139),
+            toy_point(220, 181)
+        );
+        assert_eq!(toy_point(47, 71) + toy_point(17, 56), toy_point(215, 68));
+        assert_eq!(toy_point(143, 98) + toy_point(76, 66), toy_point(47, 71));
+    }
+
+    #[test]
+    fn test_toy_curve_order() {
+        assert_eq!(
+            ToyCurve223::generator() * ToyCurve223::order(),
+            Point::Infinity
+        );
+        assert_eq!(
+            ToyCurve13::generator() * ToyCurve13::order(),
+            Point::Infinity
+        );
+
+        // Brute force the number of points, counting the point at infinity
+        let mut count = 1;
+        for x in 0..223 {
+            for y in 0..223 {
+                if Point::<ToyCurve223>::new_point(felt!(x, F223), felt!(y, F223)).is_ok() {
+                    count += 1;
+                }
+            }
+        }
+        assert_eq!(
+            Integer::from(count),
+            ToyCurve223::order().clone() * ToyCurve223::cofactor()
+        );
+        let mut count = 1;
+        for x in 0..13 {
+            for y in 0..13 {
+                if Point::<ToyCurve13>::new_point(felt!(x, F13), felt!(y, F13)).is_ok() {
+                    count += 1;
+                }
+            }
+        }
+        assert_eq!(
+            Integer::from(count),
+            ToyCurve13::order().clone() * ToyCurve13::cofactor()
+        );
+    }
+
+    #[test]
+    fn test_from_x() {
+        let (x, y) = match &*G {
+            Point::Point(x, y) => (x.clone(), y.clone()),
+            Point::Infinity => unreachable!(),
+        };
+        // G has an even y
+        assert_eq!(Point::from_x(x.clone(), false).unwrap(), *G);
+        assert_eq!(Point::lift_x(x.clone()).unwrap(), *G);
+        assert_eq!(
+            Point::<Secp256k1>::from_x(x.clone(), true).unwrap(),
+            Point::Point(x, felt!(0) - y)
+        );
+        // x = -2 gives x**3 + 7 = -1, which isn't a square
+        assert!(Point::<Secp256k1>::lift_x(felt_str!(
+            "115792089237316195423570985008687907853269984665640564039457584007908834671661"
+        ))
+        .is_err());
+
+        // Every point of the toy curve can be recovered from x and the parity of y
+        let mut found = 0;
+        for x in 0..223 {
+            for y in 0..223 {
+                if let Ok(point) = Point::<ToyCurve223>::new_point(felt!(x, F223), felt!(y, F223)) {
+                    assert_eq!(Point::from_x(felt!(x, F223), y % 2 == 1).unwrap(), point);
+                    found += 1;
+                }
+            }
+            let on_curve = (0..223)
+                .any(|y| Point::<ToyCurve223>::new_point(felt!(x, F223), felt!(y, F223)).is_ok());
+            assert_eq!(
+                Point::<ToyCurve223>::lift_x(felt!(x, F223)).is_ok(),
+                on_curve
+            );
+        }
+        assert_eq!(found, 21 * 12 - 1);
+    }
+
+    #[test]
+    fn test_group_law() {
+        let p = &*G * &Integer::from(0x8CEF_7310_u32);
+        let q = &*G * &Integer::from(12345);
+
+        assert_eq!(&p + &(-&p), Point::Infinity);
+        assert_eq!(-Point::<Secp256k1>::Infinity, Point::Infinity);
+        assert_eq!(&(&p - &q) + &q, p);
+        assert_eq!(p.clone() - p.clone(), Point::Infinity);
+        assert_eq!(&p + &Point::Infinity, p);
+        assert_eq!(&p - &Point::Infinity, p);
+
+        let mut sum = p.clone();
+        sum += &q;
+        sum += q.clone();
+        sum -= &p;
+        assert_eq!(sum, &q * &Integer::from(2));
+        sum -= q.clone();
+        assert_eq!(sum, q);
+
+        assert_eq!([p.clone(), q.clone(), -p.clone()].iter().sum::<Point>(), q);
+        assert_eq!(
+            (1..=10).map(|k| &*G * &Integer::from(k)).sum::<Point>(),
+            &*G * &Integer::from(55)
+        );
+        assert_eq!(
+            Vec::<Point>::new().into_iter().sum::<Point>(),
+            Point::Infinity
+        );
+    }
+
+    #[test]
+    fn test_zero_and_negative_scalars() {
+        let p = &*G * &Integer::from(0x8CEF_7310_u32);
+        for point in [&*G, &p, &Point::Infinity] {
+            assert_eq!(point * &Integer::from(0), Point::Infinity);
+            assert_eq!(point.mul_secret(&Integer::from(0)), Point::Infinity);
+            assert_eq!(point * &Integer::from(-7), -(point * &Integer::from(7)));
+            assert_eq!(
+                point.mul_secret(&Integer::from(-7)),
+                -(point * &Integer::from(7))
+            );
+            assert_eq!(point * &(N.clone() + 3), point * &Integer::from(3));
+        }
+        assert_eq!(
+            Point::<Secp256k1>::Infinity * &Integer::from(5),
+            Point::Infinity
+        );
+
+        // Toy curve points outside the subgroup of the generator, where k can't be reduced
+        // modulo the order of the generator
+        let point = toy_point(192, 105);
+        let mut expected = Point::Infinity;
+        for k in 0..30 {
+            assert_eq!(&point * &Integer::from(k), expected);
+            assert_eq!(point.mul_secret(&Integer::from(k)), expected);
+            assert_eq!(&point * &Integer::from(-k), -&expected);
+            assert_eq!(point.mul_secret(&Integer::from(-k)), -&expected);
+            expected += &point;
+        }
+
+        // The points of order 2 of the toy curve, which the ladder can't multiply
+        for x in [6, 11, 206] {
+            let point = toy_point(x, 0);
+            for k in -3..=3 {
+                assert_eq!(
+                    point.mul_secret(&Integer::from(k)),
+                    &point * &Integer::from(k)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        let infinity = Point::<Secp256k1>::Infinity;
+        assert!(infinity.is_infinity());
+        assert_eq!(infinity.get_x(), None);
+        assert_eq!(infinity.get_y(), None);
+        assert_eq!(infinity.tangent_slope(), None);
+        assert_eq!(G.slope(&infinity), None);
+        assert_eq!(G.slope(&-&*G), None);
+        assert!(G.get_x().is_some() && G.get_y().is_some());
+        assert!(!G.is_infinity());
+
+        // Points with y = 0 have a vertical tangent, the toy curve over F223 has three of them
+        let mut doubled_to_infinity = 0;
+        for x in 0..223 {
+            if let Ok(point) = Point::<ToyCurve223>::new_point(felt!(x, F223), felt!(0, F223)) {
+                assert_eq!(point.tangent_slope(), None);
+                assert_eq!(&point + &point, Point::Infinity);
+                doubled_to_infinity += 1;
+            }
+        }
+        assert_eq!(doubled_to_infinity, 3);
+    }
+
+    #[test]
+    fn test_fallible_constructors() {
+        assert_eq!(
+            Point::<ToyCurve223>::try_from((felt!(192, F223), felt!(105, F223))),
+            Ok(toy_point(192, 105))
+        );
+        assert_eq!(
+            Point::<ToyCurve223>::try_from((felt!(200, F223), felt!(119, F223))),
+            Err(PointNotInTheCurve)
+        );
+        assert_eq!(
+            Point::<ToyCurve223>::point_from_hex("0xc0".to_owned(), "0x69".to_owned()),
+            Ok(toy_point(192, 105))
+        );
+        assert_eq!(
+            Point::<ToyCurve223>::point_from_hex("0xc0".to_owned(), "0x169".to_owned()),
+            Err(BitcoinError::FieldElementOutOfRange)
+        );
+        assert!(matches!(
+            Point::<ToyCurve223>::point_from_hex("c0".to_owned(), "0xhi".to_owned()),
+            Err(BitcoinError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_sec() {
+        let uncompressed = [
+            (Integer::from(5000), "04ffe558e388852f0120e46af2d1b370f85854a8eb0841811ece0e3e03d282d57c315dc72890a4f10a1481c031b03b351b0dc79901ca18a00cf009dbdb157a1d10"),
+            (Integer::from(2018_u64.pow(5)), "04027f3da1918455e03c46f659266a1bb5204e959db7364d2f473bdf8f0a13cc9dff87647fd023c13b4a4994f17691895806e1b40b57f4fd22581a4f46851f3b06"),
+            (Integer::from(0xd_eadb_eef1_2345_i64), "04d90cd625ee87dd38656dd95cf79f65f60f7273b67d3096e68bd81e4f5342691f842efa762fd59961d0e99803c61edba8b3e3f7dc3a341836f97733aebf987121"),
+        ];
+        for (secret, sec) in uncompressed {
+            let point = &*G * &secret;
+            assert_eq!(encode_hex(&point.sec(false)), sec);
+            assert_eq!(Point::parse_sec(&decode_hex(sec).unwrap()).unwrap(), point);
+        }
+        let compressed = [
+            (
+                Integer::from(5001),
+                "0357a4f368868a8a6d572991e484e664810ff14c05c0fa023275251151fe0e53d1",
+            ),
+            (
+                Integer::from(2019_u64.pow(5)),
+                "02933ec2d2b111b92737ec12f1c5d20f3233a0ad21cd8b36d0bca7a0cfa5cb8701",
+            ),
+            (
+                Integer::from(0xd_eadb_eef5_4321_i64),
+                "0296be5b1292f6c856b3c5654e886fc13511462059089cdf9c479623bfcbe77690",
+            ),
+        ];
+        for (secret, sec) in compressed {
+            let point = &*G * &secret;
+            assert_eq!(encode_hex(&point.sec(true)), sec);
+            assert_eq!(Point::parse_sec(&decode_hex(sec).unwrap()).unwrap(), point);
+        }
+        assert_eq!(Point::<Secp256k1>::Infinity.sec(true), [0]);
+        assert_eq!(Point::<Secp256k1>::parse_sec(&[0]), Ok(Point::Infinity));
+
+        let p = toy_point(192, 105);
+        assert_eq!(p.sec(true), [3, 192]);
+        assert_eq!(p.sec(false), [4, 192, 105]);
+        assert_eq!(Point::parse_sec(&[3, 192]), Ok(p));
+    }
+
+    #[test]
+    fn test_parse_invalid_sec() {
+        let g_sec = G.sec(false);
+        assert_eq!(Point::<Secp256k1>::parse_sec(&[]), Err(InvalidSecLength(0)));
+        assert_eq!(
+            Point::<Secp256k1>::parse_sec(&[0, 0]),
+            Err(InvalidSecLength(2))
+        );
+        assert_eq!(
+            Point::<Secp256k1>::parse_sec(&g_sec[..33]),
+            Err(InvalidSecLength(33))
+        );
+        assert_eq!(
+            Point::<Secp256k1>::parse_sec(&G.sec(true)[..32]),
+            Err(InvalidSecLength(32))
+        );
+        for prefix in [1, 5, 6, 7, 0xff] {
+            let mut bytes = g_sec.clone();
+            bytes[0] = prefix;
+            assert_eq!(
+                Point::<Secp256k1>::parse_sec(&bytes),
+                Err(InvalidSecPrefix(prefix))
+            );
+        }
+        // y changed in the last bit
+        let mut off_curve = g_sec;
+        off_curve[64] ^= 1;
+        assert_eq!(
+            Point::<Secp256k1>::parse_sec(&off_curve),
+            Err(PointNotInTheCurve)
+        );
+        // No point has x = 5
+        let mut no_root = vec![2; 33];
+        no_root[1..].copy_from_slice(&to_fixed_bytes(&Integer::from(5), 32));
+        assert_eq!(
+            Point::<Secp256k1>::parse_sec(&no_root),
+            Err(PointNotInTheCurve)
+        );
+        // x must be below p
+        let mut too_large = vec![0xff; 33];
+        too_large[0] = 2;
+        assert_eq!(
+            Point::<Secp256k1>::parse_sec(&too_large),
+            Err(FieldElementOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_address() {
+        let vectors = [
+            (
+                Integer::from(5002),
+                false,
+                Network::Testnet,
+                "mmTPbXQFxboEtNRkwfh6K51jvdtHLxGeMA",
+            ),
+            (
+                Integer::from(2020_u64.pow(5)),
+                true,
+                Network::Testnet,
+                "mopVkxp8UhXqRYbCYJsbeE1h1fiF64jcoH",
+            ),
+            (
+                Integer::from(0x1_2345_dead_beef_i64),
+                true,
+                Network::Mainnet,
+                "1F1Pn2y6pDb68E5nYJJeba4TLg2U7B6KF1",
+            ),
+        ];
+        for (secret, compressed, network, address) in vectors {
+            assert_eq!(G.mul_secret(&secret).address(compressed, network), address);
+        }
+        assert_eq!(
+            encode_hex(&(&*G * &Integer::from(5002)).hash160(false)),
+            "41243614aecd13819d7a7f348a4a07fbcb29d8e5"
+        );
+    }
+
+    #[test]
+    fn test_batch_mul() {
+        let scalars: Vec<Integer> = [0i64, 1, 2, -3, 0x8CEF_7310]
+            .into_iter()
+            .map(Integer::from)
+            .chain([N.clone(), N.clone() - 1])
+            .collect();
+        let p = &*G * &Integer::from(12345);
+        for point in [&*G, &p, &Point::Infinity] {
+            let expected: Vec<Point> = scalars.iter().map(|k| point * k).collect();
+            assert_eq!(point.mul_batch(&scalars), expected);
+            assert_eq!(point.mul_secret_batch(&scalars), expected);
+        }
+        assert!(G.mul_batch(&[]).is_empty());
+
+        let point = toy_point(192, 105);
+        let scalars: Vec<Integer> = (-30..30).map(Integer::from).collect();
+        let expected: Vec<Point<ToyCurve223>> = scalars.iter().map(|k| &point * k).collect();
+        assert_eq!(point.mul_batch(&scalars), expected);
+        assert_eq!(point.mul_secret_batch(&scalars), expected);
+    }
+
     #[test]
     fn test_g_mul_n() {
         dbg!(&*G);
@@ -186,6 +814,11 @@ mod point_tests {
 
         for (n, result) in results {
             assert_eq!(&*G * &n, result);
+            assert_eq!(G.mul_secret(&n), result);
+            // Endomorphism paths, bypassing the fixed-base table
+            assert_eq!(wnaf::mul(&n, &G).to_affine(), result);
+            assert_eq!(Point::double_scalar_mul(&n, &Integer::from(0), &G), result);
+            assert_eq!(Point::double_scalar_mul(&Integer::from(0), &n, &G), result);
         }
     }
 }
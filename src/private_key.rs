@@ -1,71 +1,422 @@
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+
 use crate::{
-    constants::{G, N},
+    address::Network,
+    bigint::{Integer, Order},
+    constants::{G, N, N_BYTES},
+    curve::Secp256k1,
+    errors::BitcoinError::{
+        self, InvalidWifCompressionFlag, InvalidWifLength, InvalidWifPrefix, ScalarOutOfRange,
+    },
     point::Point,
+    scalar::Scalar,
     signature::Signature,
+    utils::{decode_base58_checksum, encode_base58_checksum},
 };
 
-use rug::rand::{RandGen, RandState};
-use rug::Integer;
-
-struct Seed;
-impl RandGen for Seed {
-    fn gen(&mut self) -> u32 {
-        // not really random
-        0x8CEF_7310
-    }
-}
+type HmacSha256 = Hmac<Sha256>;
 
+/// The secret is kept as big-endian bytes, always in [1, N), and wiped on drop
 pub struct PrivateKey {
-    secret: Integer,
+    secret: [u8; 32],
     pub point: Point,
 }
 
 impl PrivateKey {
-    pub fn new(secret: Integer) -> PrivateKey {
-        let point = &*G * &secret;
-        PrivateKey { secret, point }
+    /// The secret must be in [1, N)
+    pub fn new(secret: Integer) -> Result<PrivateKey, BitcoinError> {
+        let bytes = Zeroizing::new(Scalar::<Secp256k1>::try_from(secret)?.to_bytes());
+        PrivateKey::from_bytes(&bytes)
     }
 
-    pub fn sing(&self, z: Integer) -> Signature {
-        let mut seed = Seed;
-        let mut rand = RandState::new_custom(&mut seed);
-        let k: Integer = N.clone().random_below_ref(&mut rand).try_into().unwrap();
-        let r = (&*G * &k).get_x().num.clone();
-        let a: Integer = N.clone() - 2;
-        let k_inv = &k.pow_mod(&a, &N).unwrap();
-        let mut s: Integer = ((z + r.clone() * &self.secret) * k_inv) % &*N;
-        if (2 * s.clone()) > *N {
-            s = &*N - s
+    /// Big-endian secret, which must be in [1, N)
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<PrivateKey, BitcoinError> {
+        if !is_valid_secret(bytes) {
+            return Err(ScalarOutOfRange);
+        }
+        Ok(PrivateKey {
+            secret: *bytes,
+            point: G.mul_secret(&secret_integer(bytes)),
+        })
+    }
+
+    /// Random key from the OS CSPRNG. 32 random bytes are drawn until they are in [1, N), which
+    /// keeps the secret uniform. A draw is out of range with probability below 2^-127
+    pub fn generate() -> PrivateKey {
+        let mut bytes = Zeroizing::new([0; 32]);
+        loop {
+            OsRng.fill_bytes(&mut *bytes);
+            if let Ok(key) = PrivateKey::from_bytes(&bytes) {
+                return key;
+            }
+        }
+    }
+
+    /// Zeroes the secret, which leaves the key unusable. Only meant for dropping
+    fn wipe(&mut self) {
+        self.secret.zeroize();
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn secret_bytes(&self) -> &[u8; 32] {
+        &self.secret
+    }
+
+    /// Wallet Import Format: Base58Check of the network prefix and the 32 bytes of the secret,
+    /// followed by 0x01 if the public key is used compressed
+    pub fn wif(&self, compressed: bool, network: Network) -> String {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(34));
+        bytes.push(network.wif_prefix());
+        bytes.extend(self.secret);
+        if compressed {
+            bytes.push(0x01);
         }
-        Signature::new(r, s)
+        encode_base58_checksum(&bytes)
+    }
+
+    /// Parses a key in Wallet Import Format, returning it with whether its public key is
+    /// compressed and its network. The secret must be in [1, N)
+    pub fn from_wif(wif: &str) -> Result<(PrivateKey, bool, Network), BitcoinError> {
+        let bytes = Zeroizing::new(decode_base58_checksum(wif)?);
+        let Some(&prefix) = bytes.first() else {
+            return Err(InvalidWifLength(0));
+        };
+        let network = Network::from_wif_prefix(prefix).ok_or(InvalidWifPrefix(prefix))?;
+        let compressed = match bytes.len() {
+            33 => false,
+            34 if bytes[33] == 0x01 => true,
+            34 => return Err(InvalidWifCompressionFlag(bytes[33])),
+            length => return Err(InvalidWifLength(length)),
+        };
+        let mut secret = Zeroizing::new([0; 32]);
+        secret.copy_from_slice(&bytes[1..33]);
+        Ok((PrivateKey::from_bytes(&secret)?, compressed, network))
+    }
+
+    /// Signs z with a deterministic nonce (RFC 6979), so the same key and z always give the
+    /// same signature and no randomness is needed
+    pub fn sign(&self, z: Integer) -> Signature {
+        self.sign_rfc6979(z, None)
+    }
+
+    #[deprecated(note = "use `sign`")]
+    pub fn sing(&self, z: Integer) -> Signature {
+        self.sign(z)
+    }
+
+    /// Like `sign`, with extra entropy mixed into the nonce as in RFC 6979 section 3.6.
+    /// The nonce stays safe even if the entropy is bad, and changes with it
+    pub fn sign_with_entropy(&self, z: Integer, extra_entropy: &[u8; 32]) -> Signature {
+        self.sign_rfc6979(z, Some(extra_entropy))
+    }
+
+    fn sign_rfc6979(&self, z: Integer, extra_entropy: Option<&[u8; 32]>) -> Signature {
+        let z = Scalar::new(z);
+        self.sign_with_nonces(&z, self.nonces(&z, extra_entropy))
+    }
+
+    /// Signs with the first nonce that gives r and s other than 0, the RFC 6979 stream never
+    /// ends so one is always found
+    fn sign_with_nonces(&self, z: &Scalar, mut nonces: impl Iterator<Item = Scalar>) -> Signature {
+        nonces
+            .find_map(|k| {
+                let r = Scalar::new(G.mul_secret(&k.num).get_x().unwrap().num.clone());
+                if r.is_zero() {
+                    return None;
+                }
+                // k is in [1, N)
+                let k_inv = k.invert().unwrap();
+                let s = (z + &(&r * &Scalar::new(secret_integer(&self.secret)))) * k_inv;
+                // Low s, (r, -s) is also valid
+                let s = if s.is_high() { -s } else { s };
+                (!s.is_zero()).then(|| Signature::new(r, s))
+            })
+            .unwrap()
+    }
+
+    /// RFC 6979 nonces with HMAC-SHA256, from the secret and z reduced mod N. The first one is
+    /// the nonce, the next ones are the candidates of step h when a nonce gives r or s = 0
+    fn nonces(&self, z: &Scalar, extra_entropy: Option<&[u8; 32]>) -> impl Iterator<Item = Scalar> {
+        let hmac = |key: &[u8], data: &[&[u8]]| -> [u8; 32] {
+            let mut mac = HmacSha256::new_from_slice(key).unwrap();
+            for bytes in data {
+                mac.update(bytes);
+            }
+            mac.finalize().into_bytes().into()
+        };
+        let z = z.to_bytes();
+        let extra_entropy: &[u8] = extra_entropy.map_or(&[], |entropy| &entropy[..]);
+
+        let mut k = [0; 32];
+        let mut v = [1; 32];
+        k = hmac(&k, &[&v, &[0], &self.secret, &z, extra_entropy]);
+        v = hmac(&k, &[&v]);
+        k = hmac(&k, &[&v, &[1], &self.secret, &z, extra_entropy]);
+        v = hmac(&k, &[&v]);
+        std::iter::from_fn(move || loop {
+            v = hmac(&k, &[&v]);
+            let candidate = Integer::from_digits(&v, Order::Msf);
+            // Ready for the next candidate, whether this one is used or not
+            k = hmac(&k, &[&v, &[0]]);
+            v = hmac(&k, &[&v]);
+            if candidate > 0 && candidate < *N {
+                return Some(Scalar::new(candidate));
+            }
+        })
+    }
+}
+
+/// Whether the bytes are in [1, N), looking at every byte whatever their value. The subtraction
+/// of N borrows out of the most significant byte exactly when the bytes are below N
+fn is_valid_secret(bytes: &[u8; 32]) -> bool {
+    let mut borrow = 0u16;
+    let mut nonzero = 0u8;
+    for (&byte, &n) in bytes.iter().zip(N_BYTES.iter()).rev() {
+        let difference = u16::from(byte).wrapping_sub(u16::from(n) + borrow);
+        borrow = difference >> 15;
+        nonzero |= byte;
+    }
+    (borrow & u16::from(nonzero != 0)) == 1
+}
+
+/// The secret as a big number, for the two operations that need one: deriving the public point
+/// and the r * secret product when signing. Neither big number backend can wipe its limbs, so
+/// this number and the copies the arithmetic makes of it aren't wiped, like the signing nonces.
+/// Everywhere else the secret only lives in wiped byte buffers
+fn secret_integer(secret: &[u8; 32]) -> Integer {
+    Integer::from_digits(secret, Order::Msf)
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+/// Leaves the secret out
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("secret", &"<redacted>")
+            .field("point", &self.point)
+            .finish()
     }
 }
 
 #[cfg(test)]
 mod point_tests {
+    use sha2::Digest;
+
+    use crate::{
+        errors::BitcoinError::InvalidChecksum,
+        utils::{encode_hex, parse_hex},
+    };
 
     use super::*;
 
     #[test]
     fn sign_private_key() {
-        let pk = PrivateKey::new(Integer::from(9931231));
+        let pk = PrivateKey::new(Integer::from(9931231)).unwrap();
         let msg = Integer::from(132131);
-        let signature = pk.sing(msg);
+        let signature = pk.sign(msg.clone());
         assert_eq!(
-            signature.r,
+            signature.r.num,
             Integer::from_str_radix(
-                "22710005838364618652811968071825732356514023926510712063679796318149347001383",
+                "107221301066809041624498434302583071660487891924725923388337564301348923743105",
                 10
             )
             .unwrap()
         );
         assert_eq!(
-            signature.s,
+            signature.s.num,
             Integer::from_str_radix(
-                "23034864746716448693910767004334383267938751097151656510500616870201689359769",
+                "28245090029048004152472222829226889668495996971861440014359762942204774044129",
                 10
             )
             .unwrap()
         );
+        assert!(signature.verify(&msg, &pk.point));
+        // Deterministic
+        assert_eq!(pk.sign(msg), signature);
+    }
+
+    #[test]
+    fn test_rfc6979_vectors() {
+        let n_minus_1 = format!("{:x}", N.clone() - 1);
+        let vectors = [
+            ("1", "Satoshi Nakamoto", "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15", "3045022100934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d802202442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5"),
+            ("1", "All those moments will be lost in time, like tears in rain. Time to die...", "38aa22d72376b4dbc472e06c3ba403ee0a394da63fc58d88686c611aba98d6b3", "30450221008600dbd41e348fe5c9465ab92d23e3db8b98b873beecd930736488696438cb6b0220547fe64427496db33bf66019dacbf0039c04199abb0122918601db38a72cfc21"),
+            (&n_minus_1, "Satoshi Nakamoto", "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90", "3045022100fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d002206b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed5"),
+            ("f8b8af8ce3c7cca5e300d33939540c10d45ce001b8f252bfbc57ba0342904181", "Alan Turing", "525a82b70e67874398067543fd84c83d30c175fdc45fdeee082fe13b1d7cfdf1", "304402207063ae83e7f62bbb171798131b4a0564b956930092b33b07b395615d9ec7e15c022058dfcc1e00a35e1572f366ffe34ba0fc47db1e7189759b9fb233c5b05ab388ea"),
+        ];
+        for (secret, message, k, der) in vectors {
+            let key = PrivateKey::new(parse_hex(secret).unwrap()).unwrap();
+            let z = Integer::from_digits(&Sha256::digest(message.as_bytes()), Order::Msf);
+            let mut nonces = key.nonces(&Scalar::new(z.clone()), None);
+            let nonce = nonces.next().unwrap();
+            assert_eq!(encode_hex(&nonce.to_bytes()), k);
+            let signature = key.sign(z.clone());
+            assert_eq!(encode_hex(&signature.der()), der);
+            assert!(signature.verify(&z, &key.point));
+        }
+    }
+
+    #[test]
+    fn test_nonce_retry() {
+        let secret = Scalar::new(Integer::from(5003));
+        let key = PrivateKey::new(secret.num.clone()).unwrap();
+        let first = Scalar::new(Integer::from(1234));
+        let second = Scalar::new(Integer::from(5678));
+        // With z = -r * secret, the first nonce gives s = 0 and has to be skipped
+        let r = Scalar::new(G.mul_secret(&first.num).get_x().unwrap().num.clone());
+        let z = -(&r * &secret);
+        let signature = key.sign_with_nonces(&z, [first, second.clone()].into_iter());
+        assert_eq!(signature, key.sign_with_nonces(&z, std::iter::once(second)));
+        assert_ne!(signature.r, r);
+        assert!(signature.verify(&z.num, &key.point));
+    }
+
+    #[test]
+    fn test_extra_entropy() {
+        let key = PrivateKey::new(Integer::from(1)).unwrap();
+        let z = Integer::from_digits(&Sha256::digest(b"Satoshi Nakamoto"), Order::Msf);
+        let signature = key.sign_with_entropy(z.clone(), &[1; 32]);
+        assert!(signature.verify(&z, &key.point));
+        // The entropy changes the nonce, and so r
+        assert_ne!(signature.r, key.sign(z.clone()).r);
+        assert_ne!(signature.r, key.sign_with_entropy(z.clone(), &[2; 32]).r);
+        assert_eq!(signature, key.sign_with_entropy(z, &[1; 32]));
+    }
+
+    #[test]
+    fn test_new_range() {
+        assert!(PrivateKey::new(Integer::from(1)).is_ok());
+        assert!(PrivateKey::new(N.clone() - 1).is_ok());
+        for secret in [Integer::ZERO, N.clone(), N.clone() + 1, Integer::from(-1)] {
+            assert_eq!(PrivateKey::new(secret).err(), Some(ScalarOutOfRange));
+        }
+        assert_eq!(
+            PrivateKey::from_bytes(&[0; 32]).err(),
+            Some(ScalarOutOfRange)
+        );
+        assert_eq!(
+            PrivateKey::from_bytes(&[0xff; 32]).err(),
+            Some(ScalarOutOfRange)
+        );
+
+        // Around N, where the range check has to carry the borrow through every byte
+        let mut bytes = *N_BYTES;
+        assert!(!is_valid_secret(&bytes));
+        bytes[31] -= 1;
+        assert!(is_valid_secret(&bytes));
+        bytes[31] += 2;
+        assert!(!is_valid_secret(&bytes));
+        let mut one = [0; 32];
+        one[31] = 1;
+        assert!(is_valid_secret(&one));
+        // N is 0xff..fe followed by 0xba..., so these differ from it in the middle
+        let mut bytes = [0xff; 32];
+        bytes[15] = 0xfe;
+        assert!(!is_valid_secret(&bytes));
+        bytes[16] = 0xb9;
+        assert!(is_valid_secret(&bytes));
+    }
+
+    #[test]
+    fn test_generate() {
+        let key = PrivateKey::generate();
+        let other = PrivateKey::generate();
+        assert_ne!(key.secret, other.secret);
+        assert_eq!(key.point, G.mul_secret(&secret_integer(&key.secret)));
+        let z = Integer::from(132131);
+        assert!(key.sign(z.clone()).verify(&z, &key.point));
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let key = PrivateKey::new(Integer::from(0xdead_beef_u64)).unwrap();
+        let debug = format!("{key:?}");
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains("deadbeef"));
+        assert!(!debug.contains(&0xdead_beef_u64.to_string()));
+    }
+
+    #[test]
+    fn test_wipe() {
+        let mut key = PrivateKey::new(Integer::from(5003)).unwrap();
+        assert_ne!(key.secret, [0; 32]);
+        key.wipe();
+        assert_eq!(key.secret, [0; 32]);
+    }
+
+    #[test]
+    fn test_wif() {
+        let vectors = [
+            (
+                Integer::from(5003),
+                true,
+                Network::Testnet,
+                "cMahea7zqjxrtgAbB7LSGbcQUr1uX1ojuat9jZodMN8rFTv2sfUK",
+            ),
+            (
+                Integer::from(2021_u64.pow(5)),
+                false,
+                Network::Testnet,
+                "91avARGdfge8E4tZfYLoxeJ5sGBdNJQH4kvjpWAxgzczjbCwxic",
+            ),
+            (
+                Integer::from(0x5_4321_dead_beef_i64),
+                true,
+                Network::Mainnet,
+                "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgiuQJv1h8Ytr2S53a",
+            ),
+        ];
+        for (secret, compressed, network, wif) in vectors {
+            let key = PrivateKey::new(secret).unwrap();
+            assert_eq!(key.wif(compressed, network), wif);
+            let (parsed, parsed_compressed, parsed_network) = PrivateKey::from_wif(wif).unwrap();
+            assert_eq!(parsed.secret, key.secret);
+            assert_eq!(parsed.point, key.point);
+            assert_eq!((parsed_compressed, parsed_network), (compressed, network));
+        }
+    }
+
+    #[test]
+    fn test_invalid_wif() {
+        let wif = |prefix: u8, secret: &[u8], suffix: &[u8]| {
+            encode_base58_checksum(&[&[prefix], secret, suffix].concat())
+        };
+        let one = PrivateKey::new(Integer::from(1)).unwrap().secret;
+        assert!(PrivateKey::from_wif(&wif(0x80, &one, &[1])).is_ok());
+
+        let errors = [
+            (wif(0x81, &one, &[1]), InvalidWifPrefix(0x81)),
+            (wif(0x00, &one, &[]), InvalidWifPrefix(0x00)),
+            (wif(0x80, &one, &[2]), InvalidWifCompressionFlag(0x02)),
+            (wif(0x80, &one[1..], &[]), InvalidWifLength(32)),
+            (wif(0x80, &one, &[1, 1]), InvalidWifLength(35)),
+            (encode_base58_checksum(&[]), InvalidWifLength(0)),
+            (wif(0xef, &[0; 32], &[]), ScalarOutOfRange),
+            (wif(0x80, &N.to_digits(Order::Msf), &[1]), ScalarOutOfRange),
+            (wif(0x80, &[0xff; 32], &[1]), ScalarOutOfRange),
+        ];
+        for (wif, error) in errors {
+            assert_eq!(PrivateKey::from_wif(&wif).err(), Some(error), "{wif}");
+        }
+
+        let mut corrupted = PrivateKey::new(Integer::from(5003))
+            .unwrap()
+            .wif(true, Network::Testnet);
+        corrupted.pop();
+        corrupted.push('L');
+        assert_eq!(
+            PrivateKey::from_wif(&corrupted).err(),
+            Some(InvalidChecksum)
+        );
     }
 }
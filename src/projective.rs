@@ -0,0 +1,247 @@
+use crate::{
+    bigint::Integer,
+    curve::Curve,
+    field_element::{batch_invert, FieldBackend, FieldElement},
+    point::Point,
+};
+
+/// Point in homogeneous projective coordinates, (X, Y, Z) represents the affine point (X / Z, Y / Z)
+/// and the point at infinity is (0, 1, 0).
+/// Addition uses the formulas of Renes, Costello and Batina (ePrint 2015/1060, section 3), which
+/// have no branches on the inputs and only fail to add P and Q when P - Q is a point of order 2.
+/// So they are complete on curves of odd order like secp256k1 and ToyCurve13, and inside odd
+/// order subgroups like the one of the ToyCurve223 generator, but not on all of ToyCurve223,
+/// which has 252 points
+#[derive(Clone, Debug)]
+pub(crate) struct ProjectivePoint<C: Curve> {
+    x: C::Backend,
+    y: C::Backend,
+    z: C::Backend,
+}
+
+impl<C: Curve> ProjectivePoint<C> {
+    pub(crate) fn identity() -> Self {
+        ProjectivePoint {
+            x: C::Backend::zero(),
+            y: C::Backend::one(),
+            z: C::Backend::zero(),
+        }
+    }
+
+    pub(crate) fn from_affine(x: C::Backend, y: C::Backend) -> Self {
+        ProjectivePoint {
+            x,
+            y,
+            z: C::Backend::one(),
+        }
+    }
+
+    /// a and 3 * b in the field backend, as used by the addition formulas
+    pub(crate) fn formula_constants() -> (C::Backend, C::Backend) {
+        let a = C::Backend::from(C::a().clone());
+        let b3 = C::Backend::from(C::b().clone() * FieldElement::new(Integer::from(3)));
+        (a, b3)
+    }
+
+    pub(crate) fn to_affine(&self) -> Point<C> {
+        if self.z.is_zero() {
+            return Point::Infinity;
+        }
+        let z_inv = C::Backend::one() / self.z.clone();
+        let x = self.x.clone() * z_inv.clone();
+        let y = self.y.clone() * z_inv;
+        Point::Point(x.into(), y.into())
+    }
+
+    /// Affine form of all the points with a single field inversion
+    pub(crate) fn normalize_batch(points: &[Self]) -> Vec<Point<C>> {
+        let z_values: Vec<C::Backend> = points
+            .iter()
+            .filter(|point| !point.z.is_zero())
+            .map(|point| point.z.clone())
+            .collect();
+        // z is only 0 at infinity
+        let mut z_inverses = batch_invert(&z_values).unwrap().into_iter();
+        points
+            .iter()
+            .map(|point| {
+                if point.z.is_zero() {
+                    return Point::Infinity;
+                }
+                let z_inv = z_inverses.next().unwrap();
+                let x = point.x.clone() * z_inv.clone();
+                let y = point.y.clone() * z_inv;
+                Point::Point(x.into(), y.into())
+            })
+            .collect()
+    }
+
+    /// Complete addition for any a (algorithm 1), `b3` is 3 * b
+    pub(crate) fn add(&self, other: &Self, a: &C::Backend, b3: &C::Backend) -> Self {
+        let (x1, y1, z1) = (self.x.clone(), self.y.clone(), self.z.clone());
+        let (x2, y2, z2) = (other.x.clone(), other.y.clone(), other.z.clone());
+
+        let t0 = x1.clone() * x2.clone();
+        let t1 = y1.clone() * y2.clone();
+        let t2 = z1.clone() * z2.clone();
+        let t3 = (x1.clone() + y1.clone()) * (x2.clone() + y2.clone());
+        let t3 = t3 - (t0.clone() + t1.clone());
+        let t4 = (x1 + z1.clone()) * (x2 + z2.clone());
+        let t4 = t4 - (t0.clone() + t2.clone());
+        let t5 = (y1 + z1) * (y2 + z2);
+        let t5 = t5 - (t1.clone() + t2.clone());
+        let z3 = a.clone() * t4.clone() + b3.clone() * t2.clone();
+        let x3 = t1.clone() - z3.clone();
+        let z3 = t1 + z3;
+        let y3 = x3.clone() * z3.clone();
+        let t1 = t0.clone() + t0.clone() + t0.clone();
+        let t2 = a.clone() * t2;
+        let t4 = b3.clone() * t4;
+        let t1 = t1 + t2.clone();
+        let t2 = a.clone() * (t0 - t2);
+        let t4 = t4 + t2;
+        let y3 = y3 + t1.clone() * t4.clone();
+        let x3 = t3.clone() * x3 - t5.clone() * t4;
+        let z3 = t5 * z3 + t3 * t1;
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub(crate) fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        C::Backend::conditional_swap(&mut a.x, &mut b.x, choice);
+        C::Backend::conditional_swap(&mut a.y, &mut b.y, choice);
+        C::Backend::conditional_swap(&mut a.z, &mut b.z, choice);
+    }
+
+    /// Montgomery ladder: one addition and one doubling for every bit, over a number of bits
+    /// that only depends on the curve order, with the operands picked by conditional swaps.
+    /// The two operands always differ by `self`, so the result is wrong if `self` has order 2
+    pub(crate) fn mul_ladder(&self, scalar: &Integer) -> Self {
+        let (a, b3) = Self::formula_constants();
+        let bits = C::order().significant_bits().max(scalar.significant_bits());
+
+        let mut r0 = Self::identity();
+        let mut r1 = self.clone();
+        let mut swap = false;
+        for i in (0..bits).rev() {
+            let bit = scalar.get_bit(i);
+            Self::conditional_swap(&mut r0, &mut r1, swap ^ bit);
+            swap = bit;
+            r1 = r0.add(&r1, &a, &b3);
+            r0 = r0.add(&r0, &a, &b3);
+        }
+        Self::conditional_swap(&mut r0, &mut r1, swap);
+        r0
+    }
+}
+
+impl<C: Curve> From<&Point<C>> for ProjectivePoint<C> {
+    fn from(point: &Point<C>) -> Self {
+        match point {
+            Point::Infinity => ProjectivePoint::identity(),
+            Point::Point(x, y) => ProjectivePoint {
+                x: C::Backend::from(x.clone()),
+                y: C::Backend::from(y.clone()),
+                z: C::Backend::one(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod projective_tests {
+    use crate::{
+        constants::G,
+        curve::{Secp256k1, ToyCurve13, ToyCurve223},
+        felt,
+        field_element::F223,
+    };
+
+    use super::*;
+
+    fn complete_add<C: Curve>(p: &Point<C>, q: &Point<C>) -> Point<C> {
+        let (a, b3) = ProjectivePoint::<C>::formula_constants();
+        ProjectivePoint::from(p)
+            .add(&ProjectivePoint::from(q), &a, &b3)
+            .to_affine()
+    }
+
+    #[test]
+    fn test_complete_addition() {
+        let two_g = G.clone() + G.clone();
+        assert_eq!(complete_add(&*G, &*G), two_g);
+        assert_eq!(complete_add(&*G, &two_g), two_g.clone() + G.clone());
+        assert_eq!(complete_add(&*G, &Point::Infinity), *G);
+        assert_eq!(complete_add(&Point::Infinity, &*G), *G);
+        assert_eq!(
+            complete_add::<Secp256k1>(&Point::Infinity, &Point::Infinity),
+            Point::Infinity
+        );
+        let minus_g = &*G * &(crate::constants::N.clone() - 1);
+        assert_eq!(complete_add(&*G, &minus_g), Point::Infinity);
+    }
+
+    #[test]
+    fn test_exceptional_cases() {
+        let mut points = vec![Point::<ToyCurve223>::Infinity];
+        for x in 0..223 {
+            for y in 0..223 {
+                if let Ok(point) = Point::new_point(felt!(x, F223), felt!(y, F223)) {
+                    points.push(point);
+                }
+            }
+        }
+        // The formulas only fail when P - Q has order 2, on the toy curve the points with y = 0
+        for p in &points {
+            for q in &points {
+                let difference = p.clone() + -q;
+                if matches!(&difference, Point::Point(_, y) if y.num == 0) {
+                    continue;
+                }
+                assert_eq!(complete_add(p, q), p.clone() + q.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn test_ladder_matches_double_and_add() {
+        for k in [1u64, 2, 3, 7, 255, 256, 1 << 20, 0x8CEF_7310, u64::MAX] {
+            let k = Integer::from(k);
+            assert_eq!(
+                ProjectivePoint::from(&*G).mul_ladder(&k).to_affine(),
+                &*G * &k
+            );
+        }
+        assert_eq!(
+            ProjectivePoint::from(&*G)
+                .mul_ladder(&Integer::from(0))
+                .to_affine(),
+            Point::Infinity
+        );
+    }
+
+    #[test]
+    fn test_ladder_on_toy_curves() {
+        let g = ToyCurve223::generator();
+        let mut expected = Point::Infinity;
+        for k in 0..=42 {
+            assert_eq!(
+                ProjectivePoint::from(g)
+                    .mul_ladder(&Integer::from(k))
+                    .to_affine(),
+                expected
+            );
+            expected += g.clone();
+        }
+        let g = ToyCurve13::generator();
+        assert_eq!(
+            ProjectivePoint::from(g)
+                .mul_ladder(&Integer::from(8))
+                .to_affine(),
+            g.clone()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use crate::{
+    bigint::{Complete, Integer, Order},
+    curve::{Curve, Secp256k1},
+    errors::BitcoinError::{self, ScalarOutOfRange},
+    utils::parse_integer,
+};
+
+/// Integer modulo the order n of the curve group, like private keys, nonces and the r and s
+/// values of signatures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scalar<C: Curve = Secp256k1> {
+    pub num: Integer,
+    curve: PhantomData<C>,
+}
+
+impl<C: Curve> Scalar<C> {
+    /// Reduces the number modulo n, for internal arithmetic. External numbers go through
+    /// `TryFrom`, which rejects out of range values instead
+    pub(crate) fn new(num: Integer) -> Self {
+        if num < 0 || num >= *C::order() {
+            let (_, normalized_value) = num.div_rem_euc_ref(C::order()).complete();
+            Scalar {
+                num: normalized_value,
+                curve: PhantomData,
+            }
+        } else {
+            Scalar {
+                num,
+                curve: PhantomData,
+            }
+        }
+    }
+
+    /// Big-endian bytes, which must encode a number smaller than n
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, BitcoinError> {
+        Scalar::try_from(Integer::from_digits(bytes, Order::Msf))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let digits = self.num.to_digits::<u8>(Order::Msf);
+        let mut bytes = [0; 32];
+        bytes[32 - digits.len()..].copy_from_slice(&digits);
+        bytes
+    }
+
+    pub fn order() -> &'static Integer {
+        C::order()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == Integer::ZERO
+    }
+
+    /// Whether the scalar is above n / 2, the high half that low-s signatures avoid
+    pub fn is_high(&self) -> bool {
+        Integer::from(2) * &self.num > *C::order()
+    }
+
+    /// Multiplicative inverse, None for 0
+    pub fn invert(&self) -> Option<Self> {
+        self.num.clone().invert(C::order()).ok().map(Scalar::new)
+    }
+}
+
+/// Checks that the number is in [0, n) instead of reducing it like `new`
+impl<C: Curve> TryFrom<Integer> for Scalar<C> {
+    type Error = BitcoinError;
+
+    fn try_from(num: Integer) -> Result<Self, BitcoinError> {
+        if num < 0 || num >= *C::order() {
+            return Err(ScalarOutOfRange);
+        }
+        Ok(Scalar::new(num))
+    }
+}
+
+/// Decimal, or hex with 0x prefix. The number must be smaller than n
+impl<C: Curve> FromStr for Scalar<C> {
+    type Err = BitcoinError;
+
+    fn from_str(str: &str) -> Result<Self, BitcoinError> {
+        Scalar::try_from(parse_integer(str)?)
+    }
+}
+
+impl<C: Curve> Add<Scalar<C>> for Scalar<C> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Scalar::new(self.num + other.num)
+    }
+}
+
+impl<C: Curve> Add<&Scalar<C>> for &Scalar<C> {
+    type Output = Scalar<C>;
+
+    fn add(self, other: &Scalar<C>) -> Scalar<C> {
+        Scalar::new(self.num.clone() + &other.num)
+    }
+}
+
+impl<C: Curve> Sub<Scalar<C>> for Scalar<C> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Scalar::new(self.num - other.num)
+    }
+}
+
+impl<C: Curve> Sub<&Scalar<C>> for &Scalar<C> {
+    type Output = Scalar<C>;
+
+    fn sub(self, other: &Scalar<C>) -> Scalar<C> {
+        Scalar::new(self.num.clone() - &other.num)
+    }
+}
+
+impl<C: Curve> Mul<Scalar<C>> for Scalar<C> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Scalar::new(self.num * other.num)
+    }
+}
+
+impl<C: Curve> Mul<&Scalar<C>> for &Scalar<C> {
+    type Output = Scalar<C>;
+
+    fn mul(self, other: &Scalar<C>) -> Scalar<C> {
+        Scalar::new(self.num.clone() * &other.num)
+    }
+}
+
+impl<C: Curve> Neg for Scalar<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Scalar::new(-self.num)
+    }
+}
+
+impl<C: Curve> Neg for &Scalar<C> {
+    type Output = Scalar<C>;
+
+    fn neg(self) -> Scalar<C> {
+        Scalar::new(-self.num.clone())
+    }
+}
+
+#[cfg(test)]
+mod scalar_tests {
+    use crate::{constants::N, curve::ToyCurve223};
+
+    use super::*;
+
+    #[test]
+    fn test_new_scalar() {
+        let a: Scalar = Scalar::new(N.clone() + 1);
+        assert_eq!(a.num, 1);
+        let b: Scalar = Scalar::new(Integer::from(-1));
+        assert_eq!(b.num, N.clone() - 1);
+        let c = Scalar::<ToyCurve223>::new(Integer::from(-22));
+        assert_eq!(c.num, 20);
+    }
+
+    #[test]
+    fn test_scalar_arithmetic() {
+        let a: Scalar = Scalar::new(N.clone() - 5);
+        let b: Scalar = Scalar::new(Integer::from(7));
+        assert_eq!((&a + &b).num, 2);
+        assert_eq!((&b - &a).num, 12);
+        assert_eq!((&a * &b).num, N.clone() - 35);
+        assert_eq!((-&a).num, 5);
+        assert!((-Scalar::<Secp256k1>::new(Integer::from(0))).is_zero());
+        assert_eq!(a.clone() + b.clone() - b, a);
+
+        let a_inv = a.invert().unwrap();
+        assert_eq!((a * a_inv).num, 1);
+        assert_eq!(Scalar::<Secp256k1>::new(N.clone()).invert(), None);
+
+        // Brute force inverses in the order 21 group of the toy curve, where 21 isn't prime
+        for k in 1..21 {
+            let scalar = Scalar::<ToyCurve223>::new(Integer::from(k));
+            match scalar.invert() {
+                Some(inverse) => assert_eq!((scalar * inverse).num, 1),
+                None => assert!(k % 3 == 0 || k % 7 == 0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_high() {
+        let half: Scalar = Scalar::new(N.clone() / 2);
+        assert!(!half.is_high());
+        assert!((half.clone() + Scalar::new(Integer::from(1))).is_high());
+        assert!((-half).is_high());
+    }
+
+    #[test]
+    fn test_scalar_bytes() {
+        let a: Scalar = Scalar::new(Integer::from(0x0102_0304));
+        let bytes = a.to_bytes();
+        assert_eq!(bytes[28..], [1, 2, 3, 4]);
+        assert_eq!(Scalar::from_bytes(&bytes).unwrap(), a);
+
+        let max: Scalar = Scalar::new(N.clone() - 1);
+        assert_eq!(Scalar::from_bytes(&max.to_bytes()).unwrap(), max);
+        let n_bytes: [u8; 32] = N.to_digits::<u8>(Order::Msf).try_into().unwrap();
+        assert!(Scalar::<Secp256k1>::from_bytes(&n_bytes).is_err());
+        assert!(Scalar::<Secp256k1>::from_bytes(&[0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn test_fallible_constructors() {
+        assert_eq!(
+            Scalar::try_from(Integer::from(20)),
+            Ok(Scalar::<ToyCurve223>::new(Integer::from(20)))
+        );
+        assert_eq!(
+            Scalar::<ToyCurve223>::try_from(Integer::from(21)),
+            Err(ScalarOutOfRange)
+        );
+        assert_eq!(
+            Scalar::<ToyCurve223>::try_from(Integer::from(-1)),
+            Err(ScalarOutOfRange)
+        );
+        assert_eq!(
+            "0x14".parse(),
+            Ok(Scalar::<ToyCurve223>::new(Integer::from(20)))
+        );
+        assert_eq!("21".parse::<Scalar<ToyCurve223>>(), Err(ScalarOutOfRange));
+        assert!("0x".parse::<Scalar>().is_err());
+        assert_eq!(N.to_string().parse::<Scalar>(), Err(ScalarOutOfRange));
+    }
+}
@@ -0,0 +1,392 @@
+use std::{
+    hint::black_box,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::{
+    bigint::{Integer, Order},
+    errors::BitcoinError::{self, DivisionByZero},
+    field_element::{FieldBackend, FieldElement, Secp256k1Field},
+};
+
+/// p = 2**256 - 2**32 - 977, least significant limb first
+const P: [u64; 4] = [
+    0xFFFF_FFFE_FFFF_FC2F,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// 2**256 mod p = 2**32 + 977
+const R: u64 = 0x1_0000_03D1;
+
+/// p - 2, the exponent used for inversion by Fermat's little theorem
+const P_MINUS_2: [u64; 4] = [
+    0xFFFF_FFFE_FFFF_FC2D,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// Element of the secp256k1 field stored in four 64 bit limbs, always fully reduced.
+/// Reduction uses the special form of p, so no operation allocates or goes through big integers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256k1FieldElement {
+    limbs: [u64; 4],
+}
+
+impl Secp256k1FieldElement {
+    pub const ZERO: Secp256k1FieldElement = Secp256k1FieldElement { limbs: [0; 4] };
+    pub const ONE: Secp256k1FieldElement = Secp256k1FieldElement {
+        limbs: [1, 0, 0, 0],
+    };
+
+    pub fn new(num: Integer) -> Self {
+        Secp256k1FieldElement::from(FieldElement::<Secp256k1Field>::new(num))
+    }
+
+    /// Big endian bytes, values greater or equal than p are reduced
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, chunk) in bytes.rchunks(8).enumerate() {
+            limbs[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        let (reduced, borrow) = sub_limbs(&limbs, &P);
+        if borrow == 0 {
+            limbs = reduced;
+        }
+        Secp256k1FieldElement { limbs }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, chunk) in bytes.rchunks_mut(8).enumerate() {
+            chunk.copy_from_slice(&self.limbs[i].to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs == [0; 4]
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    pub fn pow(&self, exponent: &Secp256k1FieldElement) -> Secp256k1FieldElement {
+        self.pow_limbs(&exponent.limbs)
+    }
+
+    /// Returns the multiplicative inverse, or None for zero
+    pub fn invert(&self) -> Option<Secp256k1FieldElement> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.pow_limbs(&P_MINUS_2))
+    }
+
+    /// Division that returns an error instead of panicking when `other` is 0
+    pub fn checked_div(
+        &self,
+        other: &Secp256k1FieldElement,
+    ) -> Result<Secp256k1FieldElement, BitcoinError> {
+        match other.invert() {
+            Some(inv) => Ok(*self * inv),
+            None => Err(DivisionByZero),
+        }
+    }
+
+    fn pow_limbs(&self, exponent: &[u64; 4]) -> Secp256k1FieldElement {
+        let mut result = Secp256k1FieldElement::ONE;
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                result = result.square();
+                if (limb >> bit) & 1 == 1 {
+                    result = result * *self;
+                }
+            }
+        }
+        result
+    }
+}
+
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut result = [0u64; 4];
+    let mut carry = 0;
+    for i in 0..4 {
+        let (sum, c1) = a[i].overflowing_add(b[i]);
+        let (sum, c2) = sum.overflowing_add(carry);
+        result[i] = sum;
+        carry = (c1 | c2) as u64;
+    }
+    (result, carry)
+}
+
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut result = [0u64; 4];
+    let mut borrow = 0;
+    for i in 0..4 {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow);
+        result[i] = diff;
+        borrow = (b1 | b2) as u64;
+    }
+    (result, borrow)
+}
+
+/// Reduces a + carry * 2**256, where the value is known to be below 2p.
+/// Branch free, the result is picked with a mask
+fn reduce_once(limbs: [u64; 4], carry: u64) -> [u64; 4] {
+    // Wrapping a - p is a + R (mod 2**256), which is also the reduction when carry is set
+    let (reduced, borrow) = sub_limbs(&limbs, &P);
+    select(&limbs, &reduced, carry | (borrow ^ 1))
+}
+
+/// Returns `b` if `choice` is 1 and `a` if it's 0, without branching
+fn select(a: &[u64; 4], b: &[u64; 4], choice: u64) -> [u64; 4] {
+    let mask = 0u64.wrapping_sub(black_box(choice));
+    let mut result = [0u64; 4];
+    for i in 0..4 {
+        result[i] = a[i] ^ ((a[i] ^ b[i]) & mask);
+    }
+    result
+}
+
+/// Reduces a 512 bit product using 2**256 = R (mod p)
+fn reduce_wide(wide: [u64; 8]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    let mut carry: u128 = 0;
+    for i in 0..4 {
+        let value = wide[i] as u128 + (wide[i + 4] as u128) * (R as u128) + carry;
+        limbs[i] = value as u64;
+        carry = value >> 64;
+    }
+    // carry < 2**34, fold it again
+    let fold = carry * (R as u128);
+    let (limbs, overflow) = add_limbs(&limbs, &[fold as u64, (fold >> 64) as u64, 0, 0]);
+    reduce_once(limbs, overflow)
+}
+
+impl From<FieldElement<Secp256k1Field>> for Secp256k1FieldElement {
+    fn from(element: FieldElement<Secp256k1Field>) -> Self {
+        Secp256k1FieldElement::from(&element)
+    }
+}
+
+impl From<&FieldElement<Secp256k1Field>> for Secp256k1FieldElement {
+    fn from(element: &FieldElement<Secp256k1Field>) -> Self {
+        let digits = element.num.to_digits::<u8>(Order::Msf);
+        let mut bytes = [0u8; 32];
+        bytes[32 - digits.len()..].copy_from_slice(&digits);
+        Secp256k1FieldElement::from_bytes(&bytes)
+    }
+}
+
+impl From<Secp256k1FieldElement> for FieldElement<Secp256k1Field> {
+    fn from(element: Secp256k1FieldElement) -> Self {
+        FieldElement::new(Integer::from_digits(&element.to_bytes(), Order::Msf))
+    }
+}
+
+impl FieldBackend<Secp256k1Field> for Secp256k1FieldElement {
+    fn zero() -> Self {
+        Secp256k1FieldElement::ZERO
+    }
+
+    fn one() -> Self {
+        Secp256k1FieldElement::ONE
+    }
+
+    fn is_zero(&self) -> bool {
+        Secp256k1FieldElement::is_zero(self)
+    }
+
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        let mask = 0u64.wrapping_sub(black_box(choice as u64));
+        for i in 0..4 {
+            let t = (a.limbs[i] ^ b.limbs[i]) & mask;
+            a.limbs[i] ^= t;
+            b.limbs[i] ^= t;
+        }
+    }
+}
+
+impl Add for Secp256k1FieldElement {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (limbs, carry) = add_limbs(&self.limbs, &other.limbs);
+        Secp256k1FieldElement {
+            limbs: reduce_once(limbs, carry),
+        }
+    }
+}
+
+impl Sub for Secp256k1FieldElement {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, other: Self) -> Self {
+        let (limbs, borrow) = sub_limbs(&self.limbs, &other.limbs);
+        // On borrow a - b + 2**256 - R = a - b + p
+        let mask = 0u64.wrapping_sub(black_box(borrow));
+        Secp256k1FieldElement {
+            limbs: sub_limbs(&limbs, &[R & mask, 0, 0, 0]).0,
+        }
+    }
+}
+
+impl Mul for Secp256k1FieldElement {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut wide = [0u64; 8];
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let value = (self.limbs[i] as u128) * (other.limbs[j] as u128)
+                    + wide[i + j] as u128
+                    + carry;
+                wide[i + j] = value as u64;
+                carry = value >> 64;
+            }
+            wide[i + 4] = carry as u64;
+        }
+        Secp256k1FieldElement {
+            limbs: reduce_wide(wide),
+        }
+    }
+}
+
+impl Div for Secp256k1FieldElement {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        self.checked_div(&other).expect("Division by zero")
+    }
+}
+
+impl Add for &Secp256k1FieldElement {
+    type Output = Secp256k1FieldElement;
+
+    fn add(self, other: &Secp256k1FieldElement) -> Secp256k1FieldElement {
+        *self + *other
+    }
+}
+
+impl Sub for &Secp256k1FieldElement {
+    type Output = Secp256k1FieldElement;
+
+    fn sub(self, other: &Secp256k1FieldElement) -> Secp256k1FieldElement {
+        *self - *other
+    }
+}
+
+impl Mul for &Secp256k1FieldElement {
+    type Output = Secp256k1FieldElement;
+
+    fn mul(self, other: &Secp256k1FieldElement) -> Secp256k1FieldElement {
+        *self * *other
+    }
+}
+
+impl Div for &Secp256k1FieldElement {
+    type Output = Secp256k1FieldElement;
+
+    fn div(self, other: &Secp256k1FieldElement) -> Secp256k1FieldElement {
+        *self / *other
+    }
+}
+
+#[cfg(test)]
+mod secp256k1_field_tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{constants::PRIME, felt};
+
+    use super::*;
+
+    fn random_felt() -> FieldElement {
+        let bytes: [u8; 32] = thread_rng().gen();
+        FieldElement::new(Integer::from_digits(&bytes, Order::Msf))
+    }
+
+    fn samples() -> Vec<FieldElement> {
+        let mut samples = vec![
+            felt!(0),
+            felt!(1),
+            felt!(2),
+            felt!(PRIME.clone() - 1),
+            felt!(PRIME.clone() - 2),
+            felt!(PRIME.clone() / 2),
+            felt!(Integer::from(1) << 128),
+            felt!((Integer::from(1) << 255) + 977),
+        ];
+        samples.extend((0..40).map(|_| random_felt()));
+        samples
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for a in samples() {
+            assert_eq!(FieldElement::from(Secp256k1FieldElement::from(&a)), a);
+        }
+        assert_eq!(
+            Secp256k1FieldElement::from_bytes(&[0xff; 32]),
+            Secp256k1FieldElement::new((Integer::from(1) << 256) - 1)
+        );
+    }
+
+    // FieldElement uses the big integer backend: num-bigint, or GMP with the `rug` feature
+    #[test]
+    fn test_matches_integer_implementation() {
+        let samples = samples();
+        for a in &samples {
+            for b in &samples {
+                let (fa, fb) = (
+                    Secp256k1FieldElement::from(a),
+                    Secp256k1FieldElement::from(b),
+                );
+                assert_eq!(FieldElement::from(fa + fb), a.clone() + b.clone());
+                assert_eq!(FieldElement::from(fa - fb), a - b);
+                assert_eq!(FieldElement::from(fa * fb), a * b);
+                if b.num != 0 {
+                    assert_eq!(FieldElement::from(fa / fb), a / b);
+                } else {
+                    assert_eq!(fa.checked_div(&fb), Err(DivisionByZero));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pow_matches_integer_implementation() {
+        for a in samples().iter().take(16) {
+            let e = random_felt();
+            assert_eq!(
+                FieldElement::from(
+                    Secp256k1FieldElement::from(a).pow(&Secp256k1FieldElement::from(&e))
+                ),
+                a.pow(&e)
+            );
+        }
+        assert_eq!(Secp256k1FieldElement::ZERO.invert(), None);
+    }
+
+    #[test]
+    fn test_conditional_swap() {
+        let (a, b) = (random_felt(), random_felt());
+        let (mut fa, mut fb) = (
+            Secp256k1FieldElement::from(&a),
+            Secp256k1FieldElement::from(&b),
+        );
+        Secp256k1FieldElement::conditional_swap(&mut fa, &mut fb, false);
+        assert_eq!(
+            (FieldElement::from(fa), FieldElement::from(fb)),
+            (a.clone(), b.clone())
+        );
+        Secp256k1FieldElement::conditional_swap(&mut fa, &mut fb, true);
+        assert_eq!((FieldElement::from(fa), FieldElement::from(fb)), (b, a));
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt::Write;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroizing;
+
+use crate::{
+    bigint::{Integer, Order},
+    curve::Curve,
+    errors::BitcoinError::{self, InvalidHex},
+    field_element::{FieldElement, PrimeField},
+    point::Point,
+    private_key::PrivateKey,
+    scalar::Scalar,
+    signature::Signature,
+    utils::{decode_hex, encode_hex, to_fixed_bytes},
+};
+
+// serde support, behind the `serde` feature. Everything is written as hex strings:
+// field elements and scalars with a fixed width, points as compressed SEC and private keys as
+// their secret. Signatures are a { r, s } struct of scalars.
+// Deserialization validates like the fallible constructors, so out of range numbers and points
+// off the curve are rejected
+
+/// Number from exactly `width` bytes of hex
+fn from_fixed_hex(str: &str, width: usize) -> Result<Integer, BitcoinError> {
+    let bytes = decode_hex(str)?;
+    if bytes.len() != width {
+        return Err(InvalidHex(str.to_owned()));
+    }
+    Ok(Integer::from_digits(&bytes, Order::Msf))
+}
+
+fn field_width<F: PrimeField>() -> usize {
+    F::prime().significant_digits::<u8>()
+}
+
+impl<F: PrimeField> Serialize for FieldElement<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(&to_fixed_bytes(&self.num, field_width::<F>())))
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for FieldElement<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = String::deserialize(deserializer)?;
+        from_fixed_hex(&str, field_width::<F>())
+            .and_then(FieldElement::try_from)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<C: Curve> Serialize for Scalar<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let width = C::order().significant_digits::<u8>();
+        serializer.serialize_str(&encode_hex(&to_fixed_bytes(&self.num, width)))
+    }
+}
+
+impl<'de, C: Curve> Deserialize<'de> for Scalar<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = String::deserialize(deserializer)?;
+        from_fixed_hex(&str, C::order().significant_digits::<u8>())
+            .and_then(Scalar::try_from)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<C: Curve> Serialize for Point<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(&self.sec(true)))
+    }
+}
+
+impl<'de, C: Curve> Deserialize<'de> for Point<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = String::deserialize(deserializer)?;
+        decode_hex(&str)
+            .and_then(|bytes| Point::parse_sec(&bytes))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Same { r, s } shape as the derived `Serialize`, checked like `Signature::try_from`
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawSignature {
+            r: Scalar,
+            s: Scalar,
+        }
+
+        let RawSignature { r, s } = RawSignature::deserialize(deserializer)?;
+        Signature::try_from((r.num, s.num)).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for PrivateKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Written straight into a wiped buffer, like the key's own bytes
+        let mut hex = Zeroizing::new(String::with_capacity(64));
+        for byte in self.secret_bytes() {
+            write!(hex, "{byte:02x}").unwrap();
+        }
+        serializer.serialize_str(&hex)
+    }
+}
+
+/// The secret must be 32 bytes of hex in [1, N), the public point is computed again
+impl<'de> Deserialize<'de> for PrivateKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = Zeroizing::new(String::deserialize(deserializer)?);
+        // Decoded in place instead of with `decode_hex`, whose buffers and errors would keep
+        // copies of the secret
+        if str.len() != 64 || !str.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(de::Error::custom("invalid private key hex"));
+        }
+        let mut secret = Zeroizing::new([0; 32]);
+        for (i, byte) in secret.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&str[2 * i..2 * i + 2], 16).unwrap();
+        }
+        PrivateKey::from_bytes(&secret).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod serialization_tests {
+    use serde_json::{from_str, to_string};
+
+    use crate::{
+        constants::{G, N},
+        curve::{Secp256k1, ToyCurve223},
+        field_element::F223,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_field_element_json() {
+        let a: FieldElement = FieldElement::new(Integer::from(0x0102));
+        let json = to_string(&a).unwrap();
+        assert_eq!(json, format!("\"{}0102\"", "0".repeat(60)));
+        assert_eq!(from_str::<FieldElement>(&json).unwrap(), a);
+
+        let b = FieldElement::<F223>::new(Integer::from(222));
+        assert_eq!(to_string(&b).unwrap(), "\"de\"");
+        assert_eq!(from_str::<FieldElement<F223>>("\"de\"").unwrap(), b);
+        // 223 is out of range, and the width is fixed
+        assert!(from_str::<FieldElement<F223>>("\"df\"").is_err());
+        assert!(from_str::<FieldElement<F223>>("\"00de\"").is_err());
+        assert!(from_str::<FieldElement>("\"0102\"").is_err());
+        assert!(from_str::<FieldElement>(&format!("\"{}\"", "f".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn test_point_json() {
+        let json = to_string(&*G).unwrap();
+        assert_eq!(
+            json,
+            "\"0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\""
+        );
+        assert_eq!(from_str::<Point>(&json).unwrap(), *G);
+        let minus_g = -G.clone();
+        assert_eq!(
+            from_str::<Point>(&to_string(&minus_g).unwrap()).unwrap(),
+            minus_g
+        );
+        assert_eq!(to_string(&Point::<Secp256k1>::Infinity).unwrap(), "\"00\"");
+        assert_eq!(from_str::<Point>("\"00\"").unwrap(), Point::Infinity);
+
+        let uncompressed = "\"0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+                            483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8\"";
+        assert_eq!(from_str::<Point>(uncompressed).unwrap(), *G);
+
+        let p = Point::<ToyCurve223>::new_point(
+            FieldElement::new(Integer::from(192)),
+            FieldElement::new(Integer::from(105)),
+        )
+        .unwrap();
+        assert_eq!(to_string(&p).unwrap(), "\"03c0\"");
+        assert_eq!(from_str::<Point<ToyCurve223>>("\"03c0\"").unwrap(), p);
+    }
+
+    #[test]
+    fn test_point_json_validation() {
+        // y changed in the last digit
+        let off_curve = "\"0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+                         483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b9\"";
+        assert!(from_str::<Point>(off_curve).is_err());
+        // x = 5 has no point on secp256k1
+        assert!(from_str::<Point>(&format!("\"02{}05\"", "0".repeat(62))).is_err());
+        let g_hex = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        for invalid in [
+            format!("\"05{g_hex}\""),
+            format!("\"02{g_hex}00\""),
+            format!("\"04{g_hex}\""),
+            "\"0000\"".to_owned(),
+            "\"\"".to_owned(),
+            "\"02xx\"".to_owned(),
+        ] {
+            assert!(from_str::<Point>(&invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_signature_json() {
+        let signature = Signature::try_from((Integer::from(0x1234), N.clone() - 1)).unwrap();
+        let json = to_string(&signature).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                "{{\"r\":\"{}1234\",\"s\":\"{:x}\"}}",
+                "0".repeat(60),
+                N.clone() - 1
+            )
+        );
+        assert_eq!(from_str::<Signature>(&json).unwrap(), signature);
+
+        let s_equal_to_n = format!("{{\"r\":\"{}1234\",\"s\":\"{:x}\"}}", "0".repeat(60), *N);
+        assert!(from_str::<Signature>(&s_equal_to_n).is_err());
+        let zero = "0".repeat(64);
+        let r_zero = format!("{{\"r\":\"{zero}\",\"s\":\"{}1234\"}}", "0".repeat(60));
+        let s_zero = format!("{{\"r\":\"{}1234\",\"s\":\"{zero}\"}}", "0".repeat(60));
+        assert!(from_str::<Signature>(&r_zero).is_err());
+        assert!(from_str::<Signature>(&s_zero).is_err());
+    }
+
+    #[test]
+    fn test_private_key_json() {
+        let key = PrivateKey::new(Integer::from(12345)).unwrap();
+        let json = to_string(&key).unwrap();
+        assert_eq!(json, format!("\"{}3039\"", "0".repeat(60)));
+        let parsed = from_str::<PrivateKey>(&json).unwrap();
+        assert_eq!(parsed.point, key.point);
+        assert!(from_str::<PrivateKey>(&format!("\"{}\"", "0".repeat(64))).is_err());
+        assert!(from_str::<PrivateKey>(&format!("\"{:x}\"", *N)).is_err());
+        assert!(from_str::<PrivateKey>("\"3039\"").is_err());
+        assert!(from_str::<PrivateKey>(&format!("\"{}xx\"", "0".repeat(62))).is_err());
+    }
+}
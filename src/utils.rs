@@ -1,13 +1,385 @@
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    bigint::{Integer, Order},
+    errors::BitcoinError::{
+        self, InvalidBase58Character, InvalidChecksum, InvalidHex, InvalidNumber,
+    },
+};
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Parses a hex number, with or without 0x prefix
+pub fn parse_hex(str: &str) -> Result<Integer, BitcoinError> {
+    let digits = str.strip_prefix("0x").unwrap_or(str);
+    // from_str_radix would also take signs and underscores
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(InvalidNumber(str.to_owned()));
+    }
+    Integer::from_str_radix(digits, 16).map_err(|_| InvalidNumber(str.to_owned()))
+}
+
+/// Parses a non-negative decimal number, or a hex one with 0x prefix
+pub fn parse_integer(str: &str) -> Result<Integer, BitcoinError> {
+    if str.starts_with("0x") {
+        return parse_hex(str);
+    }
+    if str.is_empty() || !str.chars().all(|c| c.is_ascii_digit()) {
+        return Err(InvalidNumber(str.to_owned()));
+    }
+    Integer::from_str_radix(str, 10).map_err(|_| InvalidNumber(str.to_owned()))
+}
+
+/// Big-endian bytes of a non-negative number, left padded with zeros to `width`.
+/// Panics if the number needs more than `width` bytes. The callers pass field elements and
+/// scalars, which are reduced below the modulus that sets the width
+pub fn to_fixed_bytes(num: &Integer, width: usize) -> Vec<u8> {
+    let digits = num.to_digits::<u8>(Order::Msf);
+    assert!(
+        digits.len() <= width,
+        "{num:#x} doesn't fit in {width} bytes"
+    );
+    let mut bytes = vec![0; width - digits.len()];
+    bytes.extend(digits);
+    bytes
+}
+
+/// Lowercase hex of the bytes, two digits each
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Bytes from hex, two digits each and without 0x prefix
+pub fn decode_hex(str: &str) -> Result<Vec<u8>, BitcoinError> {
+    // from_str_radix would also take a sign
+    if !str.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(InvalidHex(str.to_owned()));
+    }
+    (0..str.len())
+        .step_by(2)
+        .map(|i| {
+            // An odd number of digits leaves half a byte at the end
+            str.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| InvalidHex(str.to_owned()))
+        })
+        .collect()
+}
+
+/// SHA-256 applied twice
+pub fn hash256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(bytes)).into()
+}
+
+/// RIPEMD-160 of the SHA-256, used to hash public keys and scripts in addresses
+pub fn hash160(bytes: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(bytes)).into()
+}
+
+/// Base58 with Bitcoin's alphabet, which leaves out 0, O, I and l.
+/// Every leading zero byte becomes a leading 1
+pub fn encode_base58(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    // Base58 digits, least significant first. The buffer never grows, log(256) / log(58) < 1.38,
+    // so it's wiped as a whole: the bytes can be a private key in WIF
+    let mut digits = Zeroizing::new(Vec::with_capacity(bytes.len() * 138 / 100 + 1));
+    for &byte in &bytes[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut str = "1".repeat(zeros);
+    str.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|&digit| BASE58_ALPHABET[digit as usize] as char),
+    );
+    str
+}
+
+pub fn decode_base58(str: &str) -> Result<Vec<u8>, BitcoinError> {
+    let zeros = str.chars().take_while(|&c| c == '1').count();
+    // Bytes, least significant first. Sized like in `encode_base58`, log(58) / log(256) < 0.74
+    let mut bytes = Zeroizing::new(Vec::with_capacity(str.len() * 74 / 100 + 1));
+    for c in str.chars() {
+        let digit = BASE58_ALPHABET
+            .iter()
+            .position(|&digit| digit as char == c)
+            .ok_or(InvalidBase58Character(c))?;
+        let mut carry = digit as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut decoded = Vec::with_capacity(zeros + bytes.len());
+    decoded.resize(zeros, 0);
+    decoded.extend(bytes.iter().rev());
+    Ok(decoded)
+}
+
+/// Base58 of the bytes followed by the first 4 bytes of their hash256
+pub fn encode_base58_checksum(bytes: &[u8]) -> String {
+    encode_base58(&Zeroizing::new([bytes, &hash256(bytes)[..4]].concat()))
+}
+
+/// Decodes Base58Check, returning the payload without the checksum
+pub fn decode_base58_checksum(str: &str) -> Result<Vec<u8>, BitcoinError> {
+    let mut bytes = decode_base58(str)?;
+    if bytes.len() < 4 {
+        return Err(InvalidChecksum);
+    }
+    let payload_len = bytes.len() - 4;
+    if hash256(&bytes[..payload_len])[..4] != bytes[payload_len..] {
+        bytes.zeroize();
+        return Err(InvalidChecksum);
+    }
+    bytes.truncate(payload_len);
+    Ok(bytes)
+}
+
 #[macro_export]
 macro_rules! felt {
     ($val1 : expr) => {
-        FieldElement::new(Integer::from($val1))
+        FieldElement::<$crate::field_element::Secp256k1Field>::new(Integer::from($val1))
+    };
+    ($val1 : expr, $field : ty) => {
+        FieldElement::<$field>::new(Integer::from($val1))
     };
 }
 
 #[macro_export]
 macro_rules! felt_str {
     ($val1 : expr) => {
-        FieldElement::new(Integer::from_str_radix($val1, 10).unwrap())
+        FieldElement::<$crate::field_element::Secp256k1Field>::new(
+            Integer::from_str_radix($val1, 10).unwrap(),
+        )
+    };
+    ($val1 : expr, $field : ty) => {
+        FieldElement::<$field>::new(Integer::from_str_radix($val1, 10).unwrap())
     };
 }
+
+/// Declares a marker type for the prime field of the given order
+#[macro_export]
+macro_rules! prime_field {
+    ($name : ident, $prime : expr) => {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct $name;
+
+        impl $crate::field_element::PrimeField for $name {
+            fn prime() -> &'static Integer {
+                lazy_static::lazy_static! {
+                    static ref PRIME: Integer = Integer::from($prime);
+                }
+                &PRIME
+            }
+        }
+    };
+}
+
+/// Declares a marker type for a short Weierstrass curve
+#[macro_export]
+macro_rules! curve {
+    ($name : ident, $field : ty, a = $a : expr, b = $b : expr, generator = ($gx : expr, $gy : expr), order = $n : expr, cofactor = $h : expr) => {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct $name;
+
+        impl $crate::curve::Curve for $name {
+            type Field = $field;
+            type Backend = FieldElement<$field>;
+
+            fn a() -> &'static FieldElement<$field> {
+                lazy_static::lazy_static! {
+                    static ref A: FieldElement<$field> = FieldElement::new(Integer::from($a));
+                }
+                &A
+            }
+
+            fn b() -> &'static FieldElement<$field> {
+                lazy_static::lazy_static! {
+                    static ref B: FieldElement<$field> = FieldElement::new(Integer::from($b));
+                }
+                &B
+            }
+
+            fn generator() -> &'static Point<Self> {
+                lazy_static::lazy_static! {
+                    static ref G: Point<$name> = Point::new_point(
+                        FieldElement::new(Integer::from($gx)),
+                        FieldElement::new(Integer::from($gy)),
+                    )
+                    .unwrap();
+                }
+                &G
+            }
+
+            fn order() -> &'static Integer {
+                lazy_static::lazy_static! {
+                    static ref N: Integer = Integer::from($n);
+                }
+                &N
+            }
+
+            fn cofactor() -> &'static Integer {
+                lazy_static::lazy_static! {
+                    static ref H: Integer = Integer::from($h);
+                }
+                &H
+            }
+
+            fn generator_table() -> &'static $crate::generator_table::GeneratorTable<Self> {
+                lazy_static::lazy_static! {
+                    static ref TABLE: $crate::generator_table::GeneratorTable<$name> =
+                        $crate::generator_table::GeneratorTable::new($name::generator());
+                }
+                &TABLE
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod utils_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("0xff").unwrap(), 255);
+        assert_eq!(parse_hex("FF").unwrap(), 255);
+        assert_eq!(parse_hex("0x00").unwrap(), 0);
+        for invalid in ["", "0x", "0xfg", "-0x1", "0x-1", "0x1_0", " 0x1", "1x1"] {
+            assert!(
+                matches!(parse_hex(invalid), Err(InvalidNumber(_))),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer("255").unwrap(), 255);
+        assert_eq!(parse_integer("0xff").unwrap(), 255);
+        for invalid in ["", "ff", "-1", "+1", "1 ", "1_0", "0x"] {
+            assert!(
+                matches!(parse_integer(invalid), Err(InvalidNumber(_))),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hex_bytes() {
+        assert_eq!(encode_hex(&[0, 1, 0xab, 0xff]), "0001abff");
+        assert_eq!(encode_hex(&[]), "");
+        assert_eq!(decode_hex("0001abFF").unwrap(), [0, 1, 0xab, 0xff]);
+        assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+        for invalid in ["0", "0x00", "zz", "+1", "é0"] {
+            assert_eq!(decode_hex(invalid), Err(InvalidHex(invalid.to_owned())));
+        }
+    }
+
+    #[test]
+    fn test_hash256() {
+        assert_eq!(
+            encode_hex(&hash256(b"hello")),
+            "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+    }
+
+    #[test]
+    fn test_hash160() {
+        assert_eq!(
+            encode_hex(&hash160(b"")),
+            "b472a266d0bd89c13706a4132ccfb16f7c3b9fcb"
+        );
+        assert_eq!(
+            encode_hex(&hash160(b"hello")),
+            "b6a9c8c230722b7c748331a8b450f05566dc7d0f"
+        );
+    }
+
+    #[test]
+    fn test_to_fixed_bytes() {
+        assert_eq!(to_fixed_bytes(&Integer::from(0x0102), 4), [0, 0, 1, 2]);
+        assert_eq!(to_fixed_bytes(&Integer::from(0x0102), 2), [1, 2]);
+        assert_eq!(to_fixed_bytes(&Integer::ZERO, 2), [0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in 1 bytes")]
+    fn test_to_fixed_bytes_too_wide() {
+        to_fixed_bytes(&Integer::from(0x0102), 1);
+    }
+
+    #[test]
+    fn test_base58() {
+        let vectors = [
+            (
+                "7c076ff316692a3d7eb3c3bb0f8b1488cf72e1afcd929e29307032997a838a3d",
+                "9MA8fRQrT4u8Zj8ZRd6MAiiyaxb2Y1CMpvVkHQu5hVM6",
+            ),
+            (
+                "eff69ef2b1bd93a66ed5219add4fb51e11a840f404876325a1e8ffe0529a2c",
+                "4fE3H2E6XMp4SsxtwinF7w9a34ooUrwWe4WsW1458Pd",
+            ),
+            (
+                "c7207fee197d27c618aea621406f6bf5ef6fca38681d82b2f06fddbdce6feab6",
+                "EQJsjkd6JaGwxrjEhfeqPenqHwrBmPQZjJGNSCHBkcF7",
+            ),
+            ("0000287fb4cd", "11233QC4"),
+            ("000000", "111"),
+            ("", ""),
+        ];
+        for (hex, base58) in vectors {
+            let bytes = decode_hex(hex).unwrap();
+            assert_eq!(encode_base58(&bytes), base58);
+            assert_eq!(decode_base58(base58).unwrap(), bytes);
+        }
+        for (invalid, c) in [("0abc", '0'), ("abcO", 'O'), ("Il", 'I'), ("1 1", ' ')] {
+            assert_eq!(decode_base58(invalid), Err(InvalidBase58Character(c)));
+        }
+    }
+
+    #[test]
+    fn test_base58_checksum() {
+        let payload = decode_hex("00f54a5851e9372b87810a8e60cdd2e7cfd80b6e31").unwrap();
+        let address = "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs";
+        assert_eq!(encode_base58_checksum(&payload), address);
+        assert_eq!(decode_base58_checksum(address).unwrap(), payload);
+        assert_eq!(
+            encode_base58_checksum(&[0; 21]),
+            "1111111111111111111114oLvT2"
+        );
+        assert_eq!(
+            decode_base58_checksum("1111111111111111111114oLvT2").unwrap(),
+            [0; 21]
+        );
+
+        // Last character changed
+        assert_eq!(
+            decode_base58_checksum("1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAt"),
+            Err(InvalidChecksum)
+        );
+        assert_eq!(decode_base58_checksum("111"), Err(InvalidChecksum));
+        assert_eq!(
+            decode_base58_checksum("1PMycacnJaSqwwJqjawXBErnLsZ7RkXUA0"),
+            Err(InvalidBase58Character('0'))
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    bigint::{Complete, Integer},
+    curve::Curve,
+    field_element::FieldBackend,
+    jacobian::JacobianPoint,
+    point::Point,
+};
+
+/// Window width used for variable base points
+const POINT_WIDTH: u32 = 5;
+
+/// Width-w non-adjacent form of a non-negative scalar, least significant digit first.
+/// Non-zero digits are odd, smaller than 2**(w-1) in absolute value and followed by at least
+/// w - 1 zeros
+pub(crate) fn wnaf(scalar: &Integer, width: u32) -> Vec<i32> {
+    assert!(scalar >= &0, "wNAF of a negative scalar");
+    let modulus = 1i32 << width;
+    let mut k = scalar.clone();
+    let mut digits = Vec::with_capacity(k.significant_bits() as usize + 1);
+    while k != 0 {
+        if k.is_odd() {
+            let mut digit = (k.to_u32_wrapping() & (modulus as u32 - 1)) as i32;
+            if digit >= modulus / 2 {
+                digit -= modulus;
+            }
+            k -= digit;
+            digits.push(digit);
+        } else {
+            digits.push(0);
+        }
+        k >>= 1;
+    }
+    digits
+}
+
+/// P, 3P, 5P, ... (2 * count - 1)P
+pub(crate) fn odd_multiples<C: Curve>(
+    point: &JacobianPoint<C>,
+    count: usize,
+) -> Vec<JacobianPoint<C>> {
+    let double = point.double();
+    let mut multiples = Vec::with_capacity(count);
+    multiples.push(point.clone());
+    for i in 1..count {
+        let next = &multiples[i - 1] + &double;
+        multiples.push(next);
+    }
+    multiples
+}
+
+/// Odd multiples of the base point of one term of an interleaved sum
+enum OddMultiples<'a, C: Curve> {
+    /// Affine multiples from a precomputed table, with x multiplied by beta on the fly when set
+    Affine(&'a [Option<(C::Backend, C::Backend)>], Option<C::Backend>),
+    Jacobian(Vec<JacobianPoint<C>>),
+}
+
+/// k * P for one term of an interleaved sum, a negative k is handled by negating every digit
+struct Term<'a, C: Curve> {
+    digits: Vec<i32>,
+    negate: bool,
+    multiples: OddMultiples<'a, C>,
+}
+
+impl<'a, C: Curve> Term<'a, C> {
+    fn new(scalar: &Integer, width: u32, multiples: OddMultiples<'a, C>) -> Self {
+        Term {
+            digits: wnaf(&scalar.clone().abs(), width),
+            negate: scalar < &0,
+            multiples,
+        }
+    }
+
+    /// Adds the multiple for the digit at position i, if any
+    fn add_digit(&self, result: JacobianPoint<C>, i: usize) -> JacobianPoint<C> {
+        let digit = self.digits.get(i).copied().unwrap_or(0);
+        if digit == 0 {
+            return result;
+        }
+        let negative = (digit < 0) ^ self.negate;
+        let index = (digit.unsigned_abs() / 2) as usize;
+        match &self.multiples {
+            OddMultiples::Affine(table, beta) => match &table[index] {
+                Some((x, y)) => {
+                    let x = match beta {
+                        Some(beta) => beta.clone() * x.clone(),
+                        None => x.clone(),
+                    };
+                    let y = if negative {
+                        C::Backend::zero() - y.clone()
+                    } else {
+                        y.clone()
+                    };
+                    result.add_mixed(&JacobianPoint::from_affine(x, y))
+                }
+                None => result,
+            },
+            OddMultiples::Jacobian(multiples) => {
+                if negative {
+                    &result + &multiples[index].neg()
+                } else {
+                    &result + &multiples[index]
+                }
+            }
+        }
+    }
+}
+
+/// Sum of k_i * P_i, interleaving the wNAF expansions so that all terms share the doublings
+/// (Strauss-Shamir trick)
+fn interleaved_sum<C: Curve>(terms: &[Term<C>]) -> JacobianPoint<C> {
+    let length = terms
+        .iter()
+        .map(|term| term.digits.len())
+        .max()
+        .unwrap_or(0);
+    let mut result = JacobianPoint::infinity();
+    for i in (0..length).rev() {
+        result = result.double();
+        for term in terms {
+            result = term.add_digit(result, i);
+        }
+    }
+    result
+}
+
+/// Terms for k * G, split with the endomorphism when the curve has one
+fn generator_terms<C: Curve>(k: &Integer) -> Vec<Term<'static, C>> {
+    let table = C::generator_table();
+    let width = table.odd_multiples_width();
+    match C::endomorphism() {
+        Some(endomorphism) => {
+            let (k1, k2) = endomorphism.decompose(k);
+            let beta = C::Backend::from(endomorphism.beta().clone());
+            vec![
+                Term::new(
+                    &k1,
+                    width,
+                    OddMultiples::Affine(table.odd_multiples(), None),
+                ),
+                Term::new(
+                    &k2,
+                    width,
+                    OddMultiples::Affine(table.odd_multiples(), Some(beta)),
+                ),
+            ]
+        }
+        None => {
+            // G has order n, so k can be reduced, which also makes it non-negative
+            let (_, k) = k.div_rem_euc_ref(C::order()).complete();
+            vec![Term::new(
+                &k,
+                width,
+                OddMultiples::Affine(table.odd_multiples(), None),
+            )]
+        }
+    }
+}
+
+/// Terms for k * P, split with the endomorphism when the curve has one
+fn point_terms<C: Curve>(k: &Integer, point: &Point<C>) -> Vec<Term<'static, C>> {
+    let multiples = odd_multiples(&JacobianPoint::from(point), 1 << (POINT_WIDTH - 2));
+    match C::endomorphism() {
+        Some(endomorphism) => {
+            let (k1, k2) = endomorphism.decompose(k);
+            let beta = C::Backend::from(endomorphism.beta().clone());
+            let endomorphism_multiples = multiples
+                .iter()
+                .map(|multiple| multiple.endomorphism(&beta))
+                .collect();
+            vec![
+                Term::new(&k1, POINT_WIDTH, OddMultiples::Jacobian(multiples)),
+                Term::new(
+                    &k2,
+                    POINT_WIDTH,
+                    OddMultiples::Jacobian(endomorphism_multiples),
+                ),
+            ]
+        }
+        None => vec![Term::new(k, POINT_WIDTH, OddMultiples::Jacobian(multiples))],
+    }
+}
+
+/// k * P with wNAF, using the curve endomorphism when there is one. Variable time
+pub(crate) fn mul<C: Curve>(k: &Integer, point: &Point<C>) -> JacobianPoint<C> {
+    interleaved_sum(&point_terms(k, point))
+}
+
+/// Sum of k_i * P_i with all the terms in a single interleaved loop (Straus), using the
+/// generator table for multiples of G. Variable time
+pub(crate) fn straus<C: Curve>(terms: &[(&Integer, &Point<C>)]) -> JacobianPoint<C> {
+    let terms: Vec<Term<C>> = terms
+        .iter()
+        .filter(|(_, point)| !point.is_infinity())
+        .flat_map(|(k, point)| {
+            if *point == C::generator() {
+                generator_terms(k)
+            } else {
+                point_terms(k, point)
+            }
+        })
+        .collect();
+    interleaved_sum(&terms)
+}
+
+/// u * G + v * P in a single interleaved loop, using the curve endomorphism when there is one.
+/// Multiples of G come from the generator table. Variable time
+pub(crate) fn double_scalar_mul<C: Curve>(u: &Integer, v: &Integer, point: &Point<C>) -> Point<C> {
+    let mut terms = generator_terms(u);
+    terms.extend(point_terms(v, point));
+    interleaved_sum(&terms).to_affine()
+}
+
+#[cfg(test)]
+mod wnaf_tests {
+    use crate::{
+        constants::{G, N},
+        curve::ToyCurve223,
+        felt,
+        field_element::{FieldElement, F223},
+        projective::ProjectivePoint,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_wnaf_digits() {
+        for k in [1u64, 2, 7, 31, 1000, 0x8CEF_7310, u64::MAX] {
+            for width in [2, 4, 5, 8] {
+                let digits = wnaf(&Integer::from(k), width);
+                let mut value = Integer::from(0);
+                for (i, digit) in digits.iter().enumerate() {
+                    value += Integer::from(*digit) << i as u32;
+                    if *digit != 0 {
+                        assert!(digit % 2 != 0 && digit.abs() < 1 << (width - 1));
+                        for next in digits.iter().skip(i + 1).take(width as usize - 1) {
+                            assert_eq!(*next, 0);
+                        }
+                    }
+                }
+                assert_eq!(value, k);
+            }
+        }
+        assert!(wnaf(&Integer::from(0), 5).is_empty());
+    }
+
+    #[test]
+    fn test_double_scalar_mul() {
+        let point = &*G * &Integer::from(0x8CEF_7310_u32);
+        for (u, v) in [
+            (Integer::from(1), Integer::from(1)),
+            (Integer::from(0), Integer::from(5)),
+            (Integer::from(5), Integer::from(0)),
+            (N.clone() - 1, Integer::from(12345)),
+            (
+                Integer::from_str_radix("112233445566778899112233445566778899", 10).unwrap(),
+                N.clone() - 2,
+            ),
+        ] {
+            let expected = ladder(&G, &u) + ladder(&point, &v);
+            assert_eq!(double_scalar_mul(&u, &v, &point), expected);
+        }
+        // u * G + v * P at infinity
+        let minus_one = N.clone() - 1;
+        assert_eq!(
+            double_scalar_mul(&Integer::from(0x8CEF_7310_u32), &minus_one, &point),
+            Point::Infinity
+        );
+    }
+
+    #[test]
+    fn test_toy_curve_double_scalar_mul() {
+        let g = ToyCurve223::generator();
+        let p = Point::<ToyCurve223>::new_point(felt!(192, F223), felt!(105, F223)).unwrap();
+        for u in 0..25 {
+            for v in 0..25 {
+                let mut expected = Point::Infinity;
+                for _ in 0..u {
+                    expected += g.clone();
+                }
+                for _ in 0..v {
+                    expected += p.clone();
+                }
+                assert_eq!(
+                    double_scalar_mul(&Integer::from(u), &Integer::from(v), &p),
+                    expected
+                );
+            }
+        }
+    }
+
+    /// Reference results through the ladder, which doesn't share code with the wNAF path
+    fn ladder(point: &Point, k: &Integer) -> Point {
+        ProjectivePoint::from(point).mul_ladder(k).to_affine()
+    }
+}